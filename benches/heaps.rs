@@ -0,0 +1,149 @@
+use std::collections::BinaryHeap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use structures::heap::{BinomialHeap, DaryHeap, FibonacciHeap, Heap, IndexedHeap, PairingHeap, PriorityQueue};
+
+const N: usize = 200_000;
+
+fn main() {
+  let items = random(N);
+
+  println!("push and pop {} items against std", N);
+  report("BinaryHeap", push_pop_std(&items));
+  report("Heap", push_pop::<Heap<u64>>(&items));
+
+  println!("heapify {} items and pop them against std", N);
+  report("BinaryHeap", heapify_std(&items));
+  report("Heap", heapify::<2>(&items));
+
+  println!("push and pop {} items", N);
+  report("Heap", push_pop::<Heap<u64>>(&items));
+  report("PairingHeap", push_pop::<PairingHeap<u64>>(&items));
+  report("BinomialHeap", push_pop::<BinomialHeap<u64>>(&items));
+  report("FibonacciHeap", push_pop::<FibonacciHeap<u64>>(&items));
+
+  println!("push and pop {} items with D-ary heaps, where D = 4 is not faster than D = 2", N);
+  report("D = 2", push_pop::<DaryHeap<u64, 2>>(&items));
+  report("D = 3", push_pop::<DaryHeap<u64, 3>>(&items));
+  report("D = 4", push_pop::<DaryHeap<u64, 4>>(&items));
+  report("D = 8", push_pop::<DaryHeap<u64, 8>>(&items));
+  report("D = 16", push_pop::<DaryHeap<u64, 16>>(&items));
+
+  println!("heapify {} items and pop them with D-ary heaps", N);
+  report("D = 2", heapify::<2>(&items));
+  report("D = 3", heapify::<3>(&items));
+  report("D = 4", heapify::<4>(&items));
+  report("D = 8", heapify::<8>(&items));
+  report("D = 16", heapify::<16>(&items));
+
+  println!("meld {} heaps of 8 items", N / 8);
+  report("Heap", meld::<Heap<u64>>(&items));
+  report("PairingHeap", meld::<PairingHeap<u64>>(&items));
+  report("BinomialHeap", meld::<BinomialHeap<u64>>(&items));
+  report("FibonacciHeap", meld::<FibonacciHeap<u64>>(&items));
+
+  println!("decrease {} keys", N);
+  report("IndexedHeap", decrease_indexed(&items));
+  report("FibonacciHeap", decrease_fibonacci(&items));
+}
+
+fn push_pop<Q: PriorityQueue<u64> + Default>(items: &[u64]) -> Duration {
+  time(|| {
+    let mut heap = Q::default();
+    for &item in items {
+      heap.push(black_box(item));
+    }
+    while black_box(heap.pop()).is_some() {}
+  })
+}
+
+fn push_pop_std(items: &[u64]) -> Duration {
+  time(|| {
+    let mut heap = BinaryHeap::new();
+    for &item in items {
+      heap.push(black_box(item));
+    }
+    while black_box(heap.pop()).is_some() {}
+  })
+}
+
+fn heapify_std(items: &[u64]) -> Duration {
+  time(|| {
+    let mut heap = BinaryHeap::from(black_box(items.to_vec()));
+    while black_box(heap.pop()).is_some() {}
+  })
+}
+
+fn heapify<const D: usize>(items: &[u64]) -> Duration {
+  time(|| {
+    let mut heap = DaryHeap::<_, D>::from(black_box(items.to_vec()));
+    while black_box(heap.pop()).is_some() {}
+  })
+}
+
+fn meld<Q: PriorityQueue<u64> + Default>(items: &[u64]) -> Duration {
+  time(|| {
+    let mut heap = Q::default();
+    for chunk in items.chunks(8) {
+      let mut other = Q::default();
+      for &item in chunk {
+        other.push(black_box(item));
+      }
+      heap.meld(other);
+    }
+    black_box(heap.pop());
+  })
+}
+
+fn decrease_indexed(items: &[u64]) -> Duration {
+  time(|| {
+    let mut heap = IndexedHeap::new_min();
+    let handles = items.iter().map(|&item| heap.push(black_box(item))).collect::<Vec<_>>();
+    black_box(heap.pop());
+    for (handle, &item) in handles.iter().zip(items) {
+      heap.decrease_key(*handle, black_box(item / 2));
+    }
+    while black_box(heap.pop()).is_some() {}
+  })
+}
+
+fn decrease_fibonacci(items: &[u64]) -> Duration {
+  time(|| {
+    let mut heap = FibonacciHeap::new_min();
+    let handles = items.iter().map(|&item| heap.push(black_box(item))).collect::<Vec<_>>();
+    black_box(heap.pop());
+    for (handle, &item) in handles.iter().zip(items) {
+      heap.decrease_key(handle, black_box(item / 2));
+    }
+    while black_box(heap.pop()).is_some() {}
+  })
+}
+
+fn time(f: impl Fn()) -> Duration {
+  (0..5)
+    .map(|_| {
+      let start = Instant::now();
+      f();
+      start.elapsed()
+    })
+    .min()
+    .unwrap()
+}
+
+fn report(name: &str, duration: Duration) {
+  println!("  {:<16}{:>10.2?}", name, duration);
+}
+
+/// Xorshift generator, for reproducible inputs.
+fn random(n: usize) -> Vec<u64> {
+  let mut x = 0x2545_f491_4f6c_dd1du64;
+  (0..n)
+    .map(|_| {
+      x ^= x << 13;
+      x ^= x >> 7;
+      x ^= x << 17;
+      x
+    })
+    .collect()
+}
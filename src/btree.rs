@@ -0,0 +1,524 @@
+use std::fmt;
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+
+/// B-Tree Map
+///
+/// An ordered map whose order, the maximum number of children of a node, is
+/// chosen at runtime. Every node but the root holds between `⌈order / 2⌉ - 1`
+/// and `order - 1` entries.
+///
+/// # Examples
+///
+/// ```
+/// use structures::btree::BTreeMap;
+///
+/// let mut map = BTreeMap::with_order(4);
+///
+/// for x in 0..10 {
+///   map.insert(x, x * x);
+/// }
+///
+/// assert_eq!(map.get(&3), Some(&9));
+/// assert_eq!(map.range(4..7).map(|(&k, _)| k).collect::<Vec<_>>(), [4, 5, 6]);
+/// assert_eq!(map.remove(&3), Some(9));
+/// assert_eq!(map.occupancy().entries, 9);
+/// ```
+pub struct BTreeMap<K, V> {
+  order: usize,
+  len: usize,
+  root: Node<K, V>,
+}
+
+/// Node occupancy statistics of a `BTreeMap`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Occupancy {
+  pub height: usize,
+  pub nodes: usize,
+  pub entries: usize,
+  /// Number of entries all the nodes can hold.
+  pub capacity: usize,
+  /// Number of nodes by number of entries they hold.
+  pub histogram: Vec<usize>,
+}
+
+impl Occupancy {
+  /// Ratio of entries to capacity.
+  pub fn fill_factor(&self) -> f64 {
+    if self.capacity == 0 {
+      0.0
+    } else {
+      self.entries as f64 / self.capacity as f64
+    }
+  }
+}
+
+/// A node is a leaf when it has no children, and otherwise has one more child than entries.
+struct Node<K, V> {
+  entries: Vec<(K, V)>,
+  children: Vec<Self>,
+}
+
+/// Median entry and right half of a node that overflowed
+type Split<K, V> = Option<((K, V), Node<K, V>)>;
+
+const DEFAULT_ORDER: usize = 12;
+
+impl<K, V> BTreeMap<K, V> {
+  pub fn new() -> Self {
+    Self::with_order(DEFAULT_ORDER)
+  }
+
+  /// # Panics
+  ///
+  /// Panics if `order` is less than 3.
+  pub fn with_order(order: usize) -> Self {
+    assert!(order >= 3, "order must be at least 3");
+    Self { order, len: 0, root: Node::new() }
+  }
+
+  pub fn order(&self) -> usize {
+    self.order
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+    let mut stack = vec![];
+    push_leftmost(&mut stack, &self.root, 0);
+    iter(stack)
+  }
+
+  pub fn occupancy(&self) -> Occupancy {
+    let mut occupancy = Occupancy {
+      height: 0,
+      nodes: 0,
+      entries: 0,
+      capacity: 0,
+      histogram: vec![0; self.order],
+    };
+    if self.is_empty() {
+      return occupancy;
+    }
+    let mut stack = vec![(&self.root, 1)];
+    while let Some((node, depth)) = stack.pop() {
+      occupancy.height = occupancy.height.max(depth);
+      occupancy.nodes += 1;
+      occupancy.entries += node.entries.len();
+      occupancy.capacity += self.order - 1;
+      occupancy.histogram[node.entries.len()] += 1;
+      stack.extend(node.children.iter().map(|child| (child, depth + 1)));
+    }
+    occupancy
+  }
+
+  fn min_entries(&self) -> usize {
+    self.order.div_ceil(2) - 1
+  }
+}
+
+impl<K: Ord, V> BTreeMap<K, V> {
+  /// Builds a map from entries with strictly increasing keys in O(n), packing
+  /// the nodes as full as the order allows.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `order` is less than 3 or the keys are not strictly increasing.
+  pub fn from_sorted(order: usize, entries: impl IntoIterator<Item = (K, V)>) -> Self {
+    let mut map = Self::with_order(order);
+    let entries = entries.into_iter().collect::<Vec<_>>();
+    assert!(entries.windows(2).all(|w| w[0].0 < w[1].0), "keys must be strictly increasing");
+    map.len = entries.len();
+    if entries.is_empty() {
+      return map;
+    }
+
+    // Leaves hold at most `order - 1` entries and are separated by one entry each.
+    let count = (entries.len() + 1).div_ceil(order);
+    let mut entries = entries.into_iter();
+    let mut nodes = vec![];
+    let mut separators = vec![];
+    for size in distribute(map.len + 1 - count, count) {
+      nodes.push(Node {
+        entries: entries.by_ref().take(size).collect(),
+        children: vec![],
+      });
+      separators.extend(entries.next());
+    }
+
+    while nodes.len() > 1 {
+      let count = nodes.len().div_ceil(order);
+      let mut children = nodes.into_iter();
+      let mut entries = separators.into_iter();
+      nodes = vec![];
+      separators = vec![];
+      for size in distribute(children.len(), count) {
+        let node = Node {
+          entries: entries.by_ref().take(size - 1).collect(),
+          children: children.by_ref().take(size).collect(),
+        };
+        nodes.push(node);
+        separators.extend(entries.next());
+      }
+    }
+
+    map.root = nodes.pop().unwrap();
+    map
+  }
+
+  pub fn get(&self, key: &K) -> Option<&V> {
+    let mut node = &self.root;
+    loop {
+      match node.search(key) {
+        Ok(i) => return Some(&node.entries[i].1),
+        Err(_) if node.is_leaf() => return None,
+        Err(i) => node = &node.children[i],
+      }
+    }
+  }
+
+  pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+    let mut node = &mut self.root;
+    loop {
+      match node.search(key) {
+        Ok(i) => return Some(&mut node.entries[i].1),
+        Err(_) if node.is_leaf() => return None,
+        Err(i) => node = &mut node.children[i],
+      }
+    }
+  }
+
+  pub fn contains_key(&self, key: &K) -> bool {
+    self.get(key).is_some()
+  }
+
+  /// Inserts an entry, returning the previous value of `key`.
+  pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+    let (old, split) = self.root.insert(key, value, self.order);
+    if let Some((median, right)) = split {
+      let left = mem::replace(&mut self.root, Node::new());
+      self.root = Node { entries: vec![median], children: vec![left, right] };
+    }
+    if old.is_none() {
+      self.len += 1;
+    }
+    old
+  }
+
+  pub fn remove(&mut self, key: &K) -> Option<V> {
+    let (_, value) = self.root.remove(key, self.min_entries())?;
+    if self.root.entries.is_empty() && !self.root.is_leaf() {
+      self.root = self.root.children.pop().unwrap();
+    }
+    self.len -= 1;
+    Some(value)
+  }
+
+  /// Iterates the entries whose keys are in `range`, in key order.
+  pub fn range<R: RangeBounds<K>>(&self, range: R) -> impl Iterator<Item = (&K, &V)> {
+    let mut stack = vec![];
+    let mut node = &self.root;
+    loop {
+      let i = match range.start_bound() {
+        Bound::Included(start) => node.entries.partition_point(|(k, _)| k < start),
+        Bound::Excluded(start) => node.entries.partition_point(|(k, _)| k <= start),
+        Bound::Unbounded => 0,
+      };
+      stack.push((node, i));
+      if node.is_leaf() {
+        break;
+      }
+      node = &node.children[i];
+    }
+    iter(stack).take_while(move |(k, _)| match range.end_bound() {
+      Bound::Included(end) => *k <= end,
+      Bound::Excluded(end) => *k < end,
+      Bound::Unbounded => true,
+    })
+  }
+}
+
+impl<K, V> Node<K, V> {
+  fn new() -> Self {
+    Self { entries: vec![], children: vec![] }
+  }
+
+  fn is_leaf(&self) -> bool {
+    self.children.is_empty()
+  }
+
+  /// Removes the greatest entry, leaving the node for its parent to rebalance.
+  fn remove_last(&mut self, min: usize) -> (K, V) {
+    if self.is_leaf() {
+      return self.entries.pop().unwrap();
+    }
+    let i = self.children.len() - 1;
+    let entry = self.children[i].remove_last(min);
+    self.rebalance(i, min);
+    entry
+  }
+
+  /// Refills the child at `i` from a sibling, or merges it with one, if it has too few entries.
+  fn rebalance(&mut self, i: usize, min: usize) {
+    if self.children[i].entries.len() >= min {
+      return;
+    }
+    if i > 0 && self.children[i - 1].entries.len() > min {
+      let left = &mut self.children[i - 1];
+      let entry = left.entries.pop().unwrap();
+      let child = left.children.pop();
+      let separator = mem::replace(&mut self.entries[i - 1], entry);
+      let node = &mut self.children[i];
+      node.entries.insert(0, separator);
+      node.children.splice(0..0, child);
+    } else if i + 1 < self.children.len() && self.children[i + 1].entries.len() > min {
+      let right = &mut self.children[i + 1];
+      let entry = right.entries.remove(0);
+      let child = if right.is_leaf() { None } else { Some(right.children.remove(0)) };
+      let separator = mem::replace(&mut self.entries[i], entry);
+      let node = &mut self.children[i];
+      node.entries.push(separator);
+      node.children.extend(child);
+    } else {
+      let i = if i > 0 { i - 1 } else { i };
+      let right = self.children.remove(i + 1);
+      let separator = self.entries.remove(i);
+      let left = &mut self.children[i];
+      left.entries.push(separator);
+      left.entries.extend(right.entries);
+      left.children.extend(right.children);
+    }
+  }
+}
+
+impl<K: Ord, V> Node<K, V> {
+  fn search(&self, key: &K) -> Result<usize, usize> {
+    self.entries.binary_search_by(|(k, _)| k.cmp(key))
+  }
+
+  /// Inserts an entry, splitting the node into itself, a median and a right node when it overflows.
+  fn insert(&mut self, key: K, value: V, order: usize) -> (Option<V>, Split<K, V>) {
+    let i = match self.search(&key) {
+      Ok(i) => return (Some(mem::replace(&mut self.entries[i].1, value)), None),
+      Err(i) => i,
+    };
+    if self.is_leaf() {
+      self.entries.insert(i, (key, value));
+    } else {
+      let (old, split) = self.children[i].insert(key, value, order);
+      if let Some((median, right)) = split {
+        self.entries.insert(i, median);
+        self.children.insert(i + 1, right);
+      } else {
+        return (old, None);
+      }
+    }
+    if self.entries.len() < order {
+      return (None, None);
+    }
+    let mid = order / 2;
+    let entries = self.entries.split_off(mid + 1);
+    let children = if self.is_leaf() { vec![] } else { self.children.split_off(mid + 1) };
+    let median = self.entries.pop().unwrap();
+    (None, Some((median, Self { entries, children })))
+  }
+
+  fn remove(&mut self, key: &K, min: usize) -> Option<(K, V)> {
+    let (entry, i) = match self.search(key) {
+      Ok(i) if self.is_leaf() => return Some(self.entries.remove(i)),
+      Err(_) if self.is_leaf() => return None,
+      Ok(i) => {
+        let predecessor = self.children[i].remove_last(min);
+        (mem::replace(&mut self.entries[i], predecessor), i)
+      }
+      Err(i) => (self.children[i].remove(key, min)?, i),
+    };
+    self.rebalance(i, min);
+    Some(entry)
+  }
+}
+
+/// Pushes `node` at `index` and the leftmost path below its child at `index`.
+fn push_leftmost<'a, K, V>(stack: &mut Vec<(&'a Node<K, V>, usize)>, mut node: &'a Node<K, V>, mut index: usize) {
+  loop {
+    stack.push((node, index));
+    if node.is_leaf() {
+      return;
+    }
+    node = &node.children[index];
+    index = 0;
+  }
+}
+
+/// Iterates in order from a stack of nodes with the index of their next entry.
+fn iter<K, V>(mut stack: Vec<(&Node<K, V>, usize)>) -> impl Iterator<Item = (&K, &V)> {
+  std::iter::from_fn(move || loop {
+    let (node, i) = stack.pop()?;
+    if let Some((k, v)) = node.entries.get(i) {
+      if node.is_leaf() {
+        stack.push((node, i + 1));
+      } else {
+        push_leftmost(&mut stack, node, i + 1);
+      }
+      return Some((k, v));
+    }
+  })
+}
+
+/// Splits `total` into `parts` sizes differing by at most one.
+fn distribute(total: usize, parts: usize) -> impl Iterator<Item = usize> {
+  (0..parts).map(move |i| total / parts + (i < total % parts) as usize)
+}
+
+impl<K, V> Default for BTreeMap<K, V> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for BTreeMap<K, V> {
+  fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+    let mut map = Self::new();
+    for (k, v) in iter {
+      map.insert(k, v);
+    }
+    map
+  }
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for BTreeMap<K, V> {
+  fn eq(&self, other: &Self) -> bool {
+    self.len == other.len && self.iter().eq(other.iter())
+  }
+}
+
+impl<K: Eq, V: Eq> Eq for BTreeMap<K, V> {}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for BTreeMap<K, V> {
+  fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+    fmt.debug_map().entries(self.iter()).finish()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::BTreeMap;
+
+  #[test]
+  fn insert_get() {
+    for order in 3..8 {
+      let mut map = BTreeMap::with_order(order);
+      for k in (0..200).map(|x| x * 7 % 200) {
+        assert_eq!(map.insert(k, k * 10), None);
+        check(&map);
+      }
+      assert_eq!(map.len(), 200);
+      assert_eq!(map.insert(5, 0), Some(50));
+      assert_eq!(map.get(&5), Some(&0));
+      assert_eq!(map.get(&200), None);
+      *map.get_mut(&6).unwrap() += 1;
+      assert_eq!(map.get(&6), Some(&61));
+      assert!(map.iter().map(|(&k, _)| k).eq(0..200));
+    }
+  }
+
+  #[test]
+  fn remove() {
+    for order in 3..8 {
+      let mut map = (0..200).map(|k| (k, k)).collect::<BTreeMap<_, _>>();
+      map = BTreeMap::from_sorted(order, map.iter().map(|(&k, &v)| (k, v)));
+      for k in (0..200).map(|x| x * 13 % 200) {
+        assert_eq!(map.remove(&k), Some(k));
+        assert_eq!(map.remove(&k), None);
+        check(&map);
+      }
+      assert!(map.is_empty());
+      assert_eq!(map.occupancy().nodes, 0);
+    }
+  }
+
+  #[test]
+  fn range() {
+    let map = BTreeMap::from_sorted(3, (0..50).map(|k| (k * 2, ())));
+    let keys = |it: &mut dyn Iterator<Item = (&i32, &())>| it.map(|(&k, _)| k).collect::<Vec<_>>();
+    assert_eq!(keys(&mut map.range(10..16)), [10, 12, 14]);
+    assert_eq!(keys(&mut map.range(9..=16)), [10, 12, 14, 16]);
+    assert_eq!(keys(&mut map.range(95..)), [96, 98]);
+    assert_eq!(keys(&mut map.range(..3)), [0, 2]);
+    assert_eq!(keys(&mut map.range(11..12)), []);
+    assert_eq!(keys(&mut map.range(200..)), []);
+    use std::ops::Bound::*;
+    assert_eq!(keys(&mut map.range((Excluded(10), Excluded(16)))), [12, 14]);
+    assert_eq!(map.range(..).count(), 50);
+  }
+
+  #[test]
+  fn from_sorted() {
+    for order in 3..10 {
+      for n in 0..100 {
+        let map = BTreeMap::from_sorted(order, (0..n).map(|k| (k, k)));
+        check(&map);
+        assert_eq!(map.len(), n as usize);
+        assert!(map.iter().map(|(&k, _)| k).eq(0..n));
+      }
+    }
+  }
+
+  #[test]
+  #[should_panic]
+  fn from_unsorted() {
+    BTreeMap::from_sorted(4, vec![(1, ()), (3, ()), (2, ())]);
+  }
+
+  #[test]
+  fn occupancy() {
+    let map = BTreeMap::from_sorted(4, (0..15).map(|k| (k, ())));
+    let occupancy = map.occupancy();
+    assert_eq!(occupancy.height, 2);
+    assert_eq!(occupancy.nodes, 5);
+    assert_eq!(occupancy.entries, 15);
+    assert_eq!(occupancy.capacity, 15);
+    assert_eq!(occupancy.histogram, [0, 0, 0, 5]);
+    assert_eq!(occupancy.fill_factor(), 1.0);
+    let mut map = BTreeMap::with_order(4);
+    for k in 0..15 {
+      map.insert(k, ());
+    }
+    assert!(map.occupancy().fill_factor() < 1.0);
+    assert_eq!(BTreeMap::<i32, ()>::new().occupancy().height, 0);
+  }
+
+  #[test]
+  fn fmt() {
+    let map = BTreeMap::from_sorted(3, vec![(1, 'a'), (2, 'b')]);
+    assert_eq!(format!("{:?}", map), "{1: 'a', 2: 'b'}");
+  }
+
+  /// Checks entry counts, key order and that all leaves are at the same depth.
+  fn check<V>(map: &BTreeMap<i32, V>) {
+    let keys = map.iter().map(|(&k, _)| k).collect::<Vec<_>>();
+    assert!(keys.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(keys.len(), map.len());
+    let mut depths = vec![];
+    let mut stack = vec![(&map.root, 0, true)];
+    while let Some((node, depth, root)) = stack.pop() {
+      let n = node.entries.len();
+      assert!(n < map.order);
+      assert!(root || n >= map.min_entries());
+      if node.is_leaf() {
+        depths.push(depth);
+      } else {
+        assert_eq!(node.children.len(), n + 1);
+      }
+      stack.extend(node.children.iter().map(|child| (child, depth + 1, false)));
+    }
+    assert!(depths.windows(2).all(|w| w[0] == w[1]));
+  }
+}
@@ -43,11 +43,11 @@ impl<T> DoublyList<T> {
     self.len
   }
 
-  pub fn front(&self) -> Option<Ref<T>> {
+  pub fn front(&self) -> Option<Ref<'_, T>> {
     self.head.as_ref().map(Self::borrow_node_data)
   }
 
-  pub fn back(&self) -> Option<Ref<T>> {
+  pub fn back(&self) -> Option<Ref<'_, T>> {
     self.tail.as_ref().map(Self::borrow_node_data)
   }
 
@@ -67,6 +67,23 @@ impl<T> DoublyList<T> {
     self.pop_back_node().map(Self::unwrap_node_data)
   }
 
+  /// Moves all the items of `other` to the back of this list, in O(1).
+  pub fn append(&mut self, other: &mut Self) {
+    match (self.tail.take(), other.head.take()) {
+      (Some(tail), Some(head)) => {
+        head.borrow_mut().prev = Some(tail.clone());
+        tail.borrow_mut().next = Some(head);
+        self.tail = other.tail.take();
+      }
+      (tail, head) => {
+        self.tail = tail.or_else(|| other.tail.take());
+        self.head = self.head.take().or(head);
+      }
+    }
+    self.len += other.len;
+    other.len = 0;
+  }
+
   pub(crate) fn push_front_node(&mut self, node: NodeRef<T>) {
     match self.head.take() {
       Some(head) => {
@@ -98,7 +115,7 @@ impl<T> DoublyList<T> {
   }
 
   pub(crate) fn pop_front_node(&mut self) -> Option<NodeRef<T>> {
-    self.head.take().map(|head| {
+    self.head.take().inspect(|head| {
       match head.borrow_mut().next.take() {
         Some(node) => {
           node.borrow_mut().prev = None;
@@ -107,12 +124,11 @@ impl<T> DoublyList<T> {
         None => self.tail = None,
       }
       self.len -= 1;
-      head
     })
   }
 
   pub(crate) fn pop_back_node(&mut self) -> Option<NodeRef<T>> {
-    self.tail.take().map(|tail| {
+    self.tail.take().inspect(|tail| {
       match tail.borrow_mut().prev.take() {
         Some(node) => {
           node.borrow_mut().next = None;
@@ -121,7 +137,6 @@ impl<T> DoublyList<T> {
         None => self.head = None,
       }
       self.len -= 1;
-      tail
     })
   }
 
@@ -133,7 +148,7 @@ impl<T> DoublyList<T> {
     Rc::try_unwrap(node).ok().unwrap().into_inner().data
   }
 
-  pub(crate) fn borrow_node_data(node: &NodeRef<T>) -> Ref<T> {
+  pub(crate) fn borrow_node_data(node: &NodeRef<T>) -> Ref<'_, T> {
     Ref::map(node.borrow(), |node| &node.data)
   }
 
@@ -212,7 +227,7 @@ mod tests {
   fn new() {
     let list = DoublyList::<()>::new();
     assert_eq!(list.len(), 0);
-    assert_eq!(list.is_empty(), true);
+    assert!(list.is_empty());
     assert_eq!(list.front().map(|x| *x), None);
     assert_eq!(list.back().map(|x| *x), None);
   }
@@ -222,17 +237,17 @@ mod tests {
     let mut list = DoublyList::new();
     list.push_front(3);
     assert_eq!(list.len(), 1);
-    assert_eq!(list.is_empty(), false);
+    assert!(!list.is_empty());
     assert_eq!(list.front().map(|x| *x), Some(3));
     assert_eq!(list.back().map(|x| *x), Some(3));
     list.push_front(2);
     assert_eq!(list.len(), 2);
-    assert_eq!(list.is_empty(), false);
+    assert!(!list.is_empty());
     assert_eq!(list.front().map(|x| *x), Some(2));
     assert_eq!(list.back().map(|x| *x), Some(3));
     list.push_front(1);
     assert_eq!(list.len(), 3);
-    assert_eq!(list.is_empty(), false);
+    assert!(!list.is_empty());
     assert_eq!(list.front().map(|x| *x), Some(1));
     assert_eq!(list.back().map(|x| *x), Some(3));
   }
@@ -242,17 +257,17 @@ mod tests {
     let mut list = DoublyList::new();
     list.push_back(1);
     assert_eq!(list.len(), 1);
-    assert_eq!(list.is_empty(), false);
+    assert!(!list.is_empty());
     assert_eq!(list.front().map(|x| *x), Some(1));
     assert_eq!(list.back().map(|x| *x), Some(1));
     list.push_back(2);
     assert_eq!(list.len(), 2);
-    assert_eq!(list.is_empty(), false);
+    assert!(!list.is_empty());
     assert_eq!(list.front().map(|x| *x), Some(1));
     assert_eq!(list.back().map(|x| *x), Some(2));
     list.push_back(3);
     assert_eq!(list.len(), 3);
-    assert_eq!(list.is_empty(), false);
+    assert!(!list.is_empty());
     assert_eq!(list.front().map(|x| *x), Some(1));
     assert_eq!(list.back().map(|x| *x), Some(3));
   }
@@ -267,21 +282,35 @@ mod tests {
     assert_eq!(list.len(), 3);
     assert_eq!(list.pop_front(), Some(1));
     assert_eq!(list.len(), 2);
-    assert_eq!(list.is_empty(), false);
+    assert!(!list.is_empty());
     assert_eq!(list.front().map(|x| *x), Some(2));
     assert_eq!(list.back().map(|x| *x), Some(3));
     assert_eq!(list.pop_front(), Some(2));
     assert_eq!(list.len(), 1);
-    assert_eq!(list.is_empty(), false);
+    assert!(!list.is_empty());
     assert_eq!(list.front().map(|x| *x), Some(3));
     assert_eq!(list.back().map(|x| *x), Some(3));
     assert_eq!(list.pop_front(), Some(3));
     assert_eq!(list.len(), 0);
-    assert_eq!(list.is_empty(), true);
+    assert!(list.is_empty());
     assert_eq!(list.front().map(|x| *x), None);
     assert_eq!(list.back().map(|x| *x), None);
   }
 
+  #[test]
+  fn append() {
+    let mut list = (1..3).collect::<DoublyList<_>>();
+    let mut other = (3..5).collect::<DoublyList<_>>();
+    list.append(&mut other);
+    assert_eq!(list.len(), 4);
+    assert!(other.is_empty());
+    assert_eq!(other.back().map(|x| *x), None);
+    list.append(&mut other);
+    other.append(&mut list);
+    assert!(list.is_empty());
+    assert_eq!(other.into_iter().collect::<Vec<_>>(), [1, 2, 3, 4]);
+  }
+
   #[test]
   fn pop_back() {
     let mut list = DoublyList::new();
@@ -292,17 +321,17 @@ mod tests {
     assert_eq!(list.len(), 3);
     assert_eq!(list.pop_back(), Some(3));
     assert_eq!(list.len(), 2);
-    assert_eq!(list.is_empty(), false);
+    assert!(!list.is_empty());
     assert_eq!(list.front().map(|x| *x), Some(1));
     assert_eq!(list.back().map(|x| *x), Some(2));
     assert_eq!(list.pop_back(), Some(2));
     assert_eq!(list.len(), 1);
-    assert_eq!(list.is_empty(), false);
+    assert!(!list.is_empty());
     assert_eq!(list.front().map(|x| *x), Some(1));
     assert_eq!(list.back().map(|x| *x), Some(1));
     assert_eq!(list.pop_back(), Some(1));
     assert_eq!(list.len(), 0);
-    assert_eq!(list.is_empty(), true);
+    assert!(list.is_empty());
     assert_eq!(list.front().map(|x| *x), None);
     assert_eq!(list.back().map(|x| *x), None);
   }
@@ -60,7 +60,7 @@ impl Graph {
   }
 
   pub fn add_edge(&mut self, source: Vertex, target: Vertex, weight: Weight) {
-    self.data.entry(source).or_insert_with(HashMap::new).insert(target, weight);
+    self.data.entry(source).or_default().insert(target, weight);
   }
 }
 
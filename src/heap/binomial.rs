@@ -0,0 +1,434 @@
+use std::cell::{RefCell, UnsafeCell};
+use std::cmp::Ordering;
+use std::ptr;
+use std::rc::{Rc, Weak};
+
+use super::owner::{find, Owner};
+use super::{Compare, MaxComparator, MinComparator, PriorityQueue};
+
+/// Binomial Heap
+///
+/// A forest with at most one binomial tree of each rank, melded like the
+/// addition of binary numbers in O(log n). `push` is O(1) amortized, and key
+/// promotion sifts the item up in O(log n). Handles stay valid across melds.
+///
+/// # Examples
+///
+/// ```
+/// use structures::heap::BinomialHeap;
+///
+/// let mut a = BinomialHeap::new_min();
+/// let mut b = BinomialHeap::new_min();
+///
+/// a.push(3);
+/// a.push(1);
+/// let handle = b.push(4);
+///
+/// a.meld(b);
+/// a.decrease_key(&handle, 2);
+///
+/// assert_eq!(a.pop(), Some(1));
+/// assert_eq!(a.pop(), Some(2));
+/// assert_eq!(a.pop(), Some(3));
+/// assert_eq!(a.get(&handle), None);
+/// ```
+pub struct BinomialHeap<T, C = MaxComparator> {
+  /// Trees indexed by rank.
+  trees: Vec<Option<Rc<Node<T>>>>,
+  len: usize,
+  owner: Rc<Owner>,
+  cmp: C,
+}
+
+/// Handle to an item of a `BinomialHeap`, which stays valid until the item is removed
+pub struct BinomialHandle<T>(Weak<Entry<T>>);
+
+/// Node of a binomial tree, whose children are sorted by rank.
+///
+/// Entries are only swapped through `&mut BinomialHeap`, so no reference given
+/// out by `&self` can be invalidated.
+struct Node<T> {
+  entry: UnsafeCell<Rc<Entry<T>>>,
+  parent: RefCell<Weak<Self>>,
+  children: RefCell<Vec<Rc<Self>>>,
+}
+
+/// Item moving between nodes as it is sifted up, which handles point to.
+///
+/// Items are only written through `&mut BinomialHeap` after checking that the
+/// heap owns the entry.
+struct Entry<T> {
+  /// Taken out when the item is removed.
+  item: UnsafeCell<Option<T>>,
+  owner: RefCell<Rc<Owner>>,
+  node: RefCell<Weak<Node<T>>>,
+}
+
+impl<T: Ord> BinomialHeap<T> {
+  pub fn new() -> Self {
+    Self::with_comparator(MaxComparator)
+  }
+
+  pub fn new_min() -> BinomialHeap<T, MinComparator> {
+    BinomialHeap::with_comparator(MinComparator)
+  }
+}
+
+impl<T, C: Compare<T>> BinomialHeap<T, C> {
+  pub fn with_comparator(cmp: C) -> Self {
+    Self { trees: vec![], len: 0, owner: Owner::new(), cmp }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn peek(&self) -> Option<&T> {
+    self.top().and_then(|rank| self.trees[rank].as_ref()).map(|node| node.item())
+  }
+
+  pub fn push(&mut self, item: T) -> BinomialHandle<T> {
+    let entry = Rc::new(Entry {
+      item: UnsafeCell::new(Some(item)),
+      owner: RefCell::new(self.owner.clone()),
+      node: RefCell::new(Weak::new()),
+    });
+    let handle = BinomialHandle(Rc::downgrade(&entry));
+    let node = Rc::new(Node {
+      entry: UnsafeCell::new(entry),
+      parent: RefCell::new(Weak::new()),
+      children: RefCell::new(vec![]),
+    });
+    *node.entry().node.borrow_mut() = Rc::downgrade(&node);
+    self.meld_trees(vec![Some(node)]);
+    self.len += 1;
+    handle
+  }
+
+  pub fn pop(&mut self) -> Option<T> {
+    let top = self.top()?;
+    self.remove_root(top)
+  }
+
+  /// Moves all the items of `other` into this heap, in O(log n).
+  ///
+  /// Handles to the items of `other` become handles to items of this heap.
+  pub fn meld(&mut self, mut other: Self) {
+    other.owner.meld_into(&self.owner);
+    self.meld_trees(std::mem::take(&mut other.trees));
+    self.len += other.len;
+  }
+
+  pub fn contains(&self, handle: &BinomialHandle<T>) -> bool {
+    self.entry(handle).is_some()
+  }
+
+  pub fn get(&self, handle: &BinomialHandle<T>) -> Option<&T> {
+    // The entry is kept alive by the heap until it is removed through `&mut self`.
+    self.entry(handle).map(|entry| unsafe { &*(entry.item() as *const T) })
+  }
+
+  /// Replaces the item of `handle` with one that is not less according to the
+  /// comparator, returning the previous one, in O(log n).
+  ///
+  /// Returns `None` if the item has been removed.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `item` is less than the current item.
+  pub fn promote(&mut self, handle: &BinomialHandle<T>, item: T) -> Option<T> {
+    let entry = self.entry(handle)?;
+    assert!(self.cmp.compare(&item, entry.item()) != Ordering::Less, "item demoted");
+    let old = unsafe { (*entry.item.get()).replace(item) }?;
+    self.sift_up(&entry, false);
+    Some(old)
+  }
+
+  /// Removes the item of `handle`, in O(log n).
+  pub fn remove(&mut self, handle: &BinomialHandle<T>) -> Option<T> {
+    let entry = self.entry(handle)?;
+    let rank = self.sift_up(&entry, true).children.borrow().len();
+    self.remove_root(rank)
+  }
+
+  fn entry(&self, handle: &BinomialHandle<T>) -> Option<Rc<Entry<T>>> {
+    let entry = handle.0.upgrade()?;
+    let owner = find(&entry.owner.borrow());
+    *entry.owner.borrow_mut() = owner.clone();
+    if Rc::ptr_eq(&owner, &self.owner) && unsafe { (*entry.item.get()).is_some() } {
+      Some(entry)
+    } else {
+      None
+    }
+  }
+
+  /// Swaps an entry with the one of its parent while it is greater, or up to
+  /// the root if `to_root` is set, returning the node it ends up in.
+  fn sift_up(&mut self, entry: &Entry<T>, to_root: bool) -> Rc<Node<T>> {
+    let mut node = entry.node.borrow().upgrade().unwrap();
+    loop {
+      let parent = node.parent.borrow().upgrade();
+      let parent = match parent {
+        Some(parent) if to_root || self.cmp.compare(parent.item(), node.item()) == Ordering::Less => parent,
+        _ => return node,
+      };
+      // No reference to an entry is alive while `&mut self` is held.
+      unsafe { ptr::swap(node.entry.get(), parent.entry.get()) };
+      *node.entry().node.borrow_mut() = Rc::downgrade(&node);
+      *parent.entry().node.borrow_mut() = Rc::downgrade(&parent);
+      node = parent;
+    }
+  }
+
+  /// Removes the root of the tree of `rank`, melding its children back in.
+  fn remove_root(&mut self, rank: usize) -> Option<T> {
+    let node = self.trees[rank].take()?;
+    let children = std::mem::take(&mut *node.children.borrow_mut());
+    for child in &children {
+      *child.parent.borrow_mut() = Weak::new();
+    }
+    self.meld_trees(children.into_iter().map(Some).collect());
+    while let Some(None) = self.trees.last() {
+      self.trees.pop();
+    }
+    self.len -= 1;
+    // The node is out of the heap, so no reference to its item is left.
+    unsafe { (*node.entry().item.get()).take() }
+  }
+
+  /// Adds the trees of another forest, carrying linked trees to the next rank
+  /// until both the carry and the other forest are exhausted.
+  fn meld_trees(&mut self, trees: Vec<Option<Rc<Node<T>>>>) {
+    let mut trees = trees.into_iter();
+    let mut carry = None;
+    for rank in 0.. {
+      let tree = trees.next();
+      if tree.is_none() && carry.is_none() {
+        break;
+      }
+      if rank == self.trees.len() {
+        self.trees.push(None);
+      }
+      let mut nodes = self.trees[rank].take().into_iter().chain(tree.flatten()).chain(carry.take());
+      match (nodes.next(), nodes.next(), nodes.next()) {
+        (a, None, _) => self.trees[rank] = a,
+        (Some(a), Some(b), c) => {
+          self.trees[rank] = c;
+          carry = Some(self.link(a, b));
+        }
+        (None, Some(_), _) => unreachable!(),
+      }
+    }
+  }
+
+  /// Rank of the tree with the greatest root.
+  fn top(&self) -> Option<usize> {
+    let roots = self.trees.iter().enumerate().filter_map(|(rank, tree)| tree.as_ref().map(|node| (rank, node)));
+    let top = roots.reduce(|a, b| if self.cmp.compare(a.1.item(), b.1.item()) == Ordering::Less { b } else { a });
+    top.map(|(rank, _)| rank)
+  }
+
+  /// Links two trees of the same rank into one of the next rank.
+  fn link(&self, a: Rc<Node<T>>, b: Rc<Node<T>>) -> Rc<Node<T>> {
+    let (parent, child) = match self.cmp.compare(a.item(), b.item()) {
+      Ordering::Less => (b, a),
+      _ => (a, b),
+    };
+    *child.parent.borrow_mut() = Rc::downgrade(&parent);
+    parent.children.borrow_mut().push(child);
+    parent
+  }
+}
+
+impl<T: Ord> BinomialHeap<T, MinComparator> {
+  /// Replaces the item of `handle` with a lesser one, in O(log n).
+  ///
+  /// # Panics
+  ///
+  /// Panics if `item` is greater than the current item.
+  pub fn decrease_key(&mut self, handle: &BinomialHandle<T>, item: T) -> Option<T> {
+    self.promote(handle, item)
+  }
+}
+
+impl<T: Ord> BinomialHeap<T, MaxComparator> {
+  /// Replaces the item of `handle` with a greater one, in O(log n).
+  ///
+  /// # Panics
+  ///
+  /// Panics if `item` is less than the current item.
+  pub fn increase_key(&mut self, handle: &BinomialHandle<T>, item: T) -> Option<T> {
+    self.promote(handle, item)
+  }
+}
+
+impl<T> Node<T> {
+  fn entry(&self) -> &Entry<T> {
+    unsafe { &*self.entry.get() }
+  }
+
+  /// Item of a node that is still in a heap.
+  fn item(&self) -> &T {
+    self.entry().item()
+  }
+}
+
+impl<T> Entry<T> {
+  fn item(&self) -> &T {
+    unsafe { (*self.item.get()).as_ref().unwrap() }
+  }
+}
+
+impl<T> Clone for BinomialHandle<T> {
+  fn clone(&self) -> Self {
+    Self(self.0.clone())
+  }
+}
+
+impl<T, C: Compare<T>> PriorityQueue<T> for BinomialHeap<T, C> {
+  fn push(&mut self, item: T) {
+    BinomialHeap::push(self, item);
+  }
+
+  fn pop(&mut self) -> Option<T> {
+    BinomialHeap::pop(self)
+  }
+
+  fn peek(&self) -> Option<&T> {
+    BinomialHeap::peek(self)
+  }
+
+  fn len(&self) -> usize {
+    BinomialHeap::len(self)
+  }
+
+  fn meld(&mut self, other: Self) {
+    BinomialHeap::meld(self, other)
+  }
+}
+
+impl<T, C: Compare<T> + Default> Default for BinomialHeap<T, C> {
+  fn default() -> Self {
+    Self::with_comparator(C::default())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::BinomialHeap;
+
+  #[test]
+  fn push_pop() {
+    let mut heap = BinomialHeap::new();
+    for i in (0..100).map(|x| x * 37 % 100) {
+      heap.push(i);
+    }
+    assert_eq!(heap.len(), 100);
+    assert_eq!(heap.peek(), Some(&99));
+    for i in (0..100).rev() {
+      assert_eq!(heap.pop(), Some(i));
+    }
+    assert_eq!(heap.pop(), None);
+    assert!(heap.trees.is_empty());
+  }
+
+  #[test]
+  fn ranks() {
+    let mut heap = BinomialHeap::new();
+    for i in 0..13 {
+      heap.push(i);
+    }
+    let ranks = heap.trees.iter().map(Option::is_some).collect::<Vec<_>>();
+    assert_eq!(ranks, [true, false, true, true]);
+  }
+
+  #[test]
+  fn meld() {
+    let mut a = BinomialHeap::new_min();
+    let mut b = BinomialHeap::new_min();
+    for i in 0..10 {
+      a.push(i * 3);
+      b.push(i * 3 + 1);
+      b.push(i * 3 + 2);
+    }
+    a.meld(b);
+    a.meld(BinomialHeap::new_min());
+    assert_eq!(a.len(), 30);
+    assert_eq!(std::iter::from_fn(|| a.pop()).collect::<Vec<_>>(), (0..30).collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn decrease_key() {
+    let mut heap = BinomialHeap::new_min();
+    let handles = (0..100).map(|i| heap.push(i + 100)).collect::<Vec<_>>();
+    assert_eq!(heap.pop(), Some(100));
+    for (i, handle) in handles.iter().enumerate().skip(1) {
+      let key = if i % 3 == 0 { i as i32 } else { i as i32 + 100 };
+      assert_eq!(heap.decrease_key(handle, key), Some(i as i32 + 100));
+      assert_eq!(heap.get(handle), Some(&key));
+    }
+    let mut expected = (1..100).map(|i| if i % 3 == 0 { i } else { i + 100 }).collect::<Vec<_>>();
+    expected.sort_unstable();
+    assert_eq!(std::iter::from_fn(|| heap.pop()).collect::<Vec<_>>(), expected);
+  }
+
+  #[test]
+  #[should_panic]
+  fn promote_demoted() {
+    let mut heap = BinomialHeap::new();
+    let handle = heap.push(3);
+    heap.increase_key(&handle, 2);
+  }
+
+  #[test]
+  fn meld_handles() {
+    let mut a = BinomialHeap::new();
+    let mut b = BinomialHeap::new();
+    let c = BinomialHeap::<i32>::new();
+    let x = a.push(1);
+    let y = b.push(2);
+    assert!(!a.contains(&y));
+    assert!(!c.contains(&x));
+    a.meld(b);
+    assert!(a.contains(&y));
+    assert_eq!(a.increase_key(&x, 5), Some(1));
+    assert_eq!(a.peek(), Some(&5));
+    let mut d = BinomialHeap::new();
+    d.meld(a);
+    assert_eq!(d.get(&y), Some(&2));
+    assert_eq!(d.len(), 2);
+  }
+
+  #[test]
+  fn remove() {
+    let mut heap = BinomialHeap::new_min();
+    let handles = (0..20).map(|i| heap.push(i)).collect::<Vec<_>>();
+    heap.pop();
+    for &i in [7, 0, 19, 3, 1].iter() {
+      let expected = if i == 0 { None } else { Some(i) };
+      assert_eq!(heap.remove(&handles[i as usize]), expected);
+      assert!(!heap.contains(&handles[i as usize]));
+    }
+    assert_eq!(heap.len(), 15);
+    let items = std::iter::from_fn(|| heap.pop()).collect::<Vec<_>>();
+    assert_eq!(items, [2, 4, 5, 6, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18]);
+  }
+
+  #[test]
+  fn handles_outlive_items() {
+    let mut heap = BinomialHeap::new();
+    let handles = (0..10).map(|i| heap.push(i)).collect::<Vec<_>>();
+    let copies = handles.clone();
+    assert_eq!(heap.pop(), Some(9));
+    assert_eq!(heap.remove(&handles[4]), Some(4));
+    assert!(!heap.contains(&copies[9]));
+    assert_eq!(heap.get(&copies[4]), None);
+    assert_eq!(heap.promote(&copies[9], 20), None);
+    assert_eq!(heap.increase_key(&handles[0], 10), Some(0));
+    assert_eq!(std::iter::from_fn(|| heap.pop()).collect::<Vec<_>>(), [10, 8, 7, 6, 5, 3, 2, 1]);
+  }
+}
@@ -0,0 +1,464 @@
+use std::cell::{Cell, RefCell, UnsafeCell};
+use std::cmp::Ordering;
+use std::mem;
+use std::rc::{Rc, Weak};
+
+use super::owner::{find, Owner};
+use super::{Compare, MaxComparator, MinComparator, PriorityQueue};
+use crate::doublylist::DoublyList;
+
+/// Fibonacci Heap
+///
+/// A lazily consolidated forest with O(1) `push`, `meld` and key promotion, and
+/// O(log n) amortized `pop`. Handles stay valid across melds.
+///
+/// # Examples
+///
+/// ```
+/// use structures::heap::FibonacciHeap;
+///
+/// let mut a = FibonacciHeap::new_min();
+/// let mut b = FibonacciHeap::new_min();
+///
+/// a.push(3);
+/// let handle = b.push(5);
+///
+/// a.meld(b);
+/// a.decrease_key(&handle, 1);
+///
+/// assert_eq!(a.get(&handle), Some(&1));
+/// assert_eq!(a.pop(), Some(1));
+/// assert_eq!(a.pop(), Some(3));
+/// assert_eq!(a.get(&handle), None);
+/// ```
+pub struct FibonacciHeap<T, C = MaxComparator> {
+  roots: DoublyList<Rc<Node<T>>>,
+  /// Greatest root, which is also in `roots`.
+  top: Option<Rc<Node<T>>>,
+  len: usize,
+  owner: Rc<Owner>,
+  cmp: C,
+}
+
+/// Handle to an item of a `FibonacciHeap`, which stays valid until the item is removed
+pub struct FibonacciHandle<T>(Weak<Node<T>>);
+
+/// Items are only written through `&mut FibonacciHeap` after checking that the
+/// heap owns the node, so no reference given out by `&self` can be invalidated.
+struct Node<T> {
+  /// Taken out when the item is removed.
+  item: UnsafeCell<Option<T>>,
+  owner: RefCell<Rc<Owner>>,
+  parent: RefCell<Weak<Self>>,
+  children: RefCell<Vec<Rc<Self>>>,
+  /// Position in the children of the parent.
+  index: Cell<usize>,
+  /// Whether a child has been cut since the node became a child itself.
+  marked: Cell<bool>,
+}
+
+impl<T: Ord> FibonacciHeap<T> {
+  pub fn new() -> Self {
+    Self::with_comparator(MaxComparator)
+  }
+
+  pub fn new_min() -> FibonacciHeap<T, MinComparator> {
+    FibonacciHeap::with_comparator(MinComparator)
+  }
+}
+
+impl<T, C: Compare<T>> FibonacciHeap<T, C> {
+  pub fn with_comparator(cmp: C) -> Self {
+    Self {
+      roots: DoublyList::new(),
+      top: None,
+      len: 0,
+      owner: Owner::new(),
+      cmp,
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn peek(&self) -> Option<&T> {
+    self.top.as_ref().map(|node| node.item())
+  }
+
+  pub fn push(&mut self, item: T) -> FibonacciHandle<T> {
+    let node = Rc::new(Node {
+      item: UnsafeCell::new(Some(item)),
+      owner: RefCell::new(self.owner.clone()),
+      parent: RefCell::new(Weak::new()),
+      children: RefCell::new(vec![]),
+      index: Cell::new(0),
+      marked: Cell::new(false),
+    });
+    let handle = FibonacciHandle(Rc::downgrade(&node));
+    self.push_root(node);
+    self.len += 1;
+    handle
+  }
+
+  /// Removes the greatest item, consolidating the roots so that no two have the same rank.
+  pub fn pop(&mut self) -> Option<T> {
+    let top = self.top.take()?;
+    let mut ranks: Vec<Option<Rc<Node<T>>>> = vec![];
+    let mut roots = mem::take(&mut self.roots).into_iter().filter(|node| !Rc::ptr_eq(node, &top)).collect::<Vec<_>>();
+    roots.append(&mut top.children.borrow_mut());
+    for mut node in roots {
+      *node.parent.borrow_mut() = Weak::new();
+      node.marked.set(false);
+      loop {
+        let rank = node.children.borrow().len();
+        if rank >= ranks.len() {
+          ranks.resize_with(rank + 1, || None);
+        }
+        match ranks[rank].take() {
+          Some(other) => node = self.link(node, other),
+          None => {
+            ranks[rank] = Some(node);
+            break;
+          }
+        }
+      }
+    }
+    for node in ranks.into_iter().flatten() {
+      self.push_root(node);
+    }
+    self.len -= 1;
+    // The node is out of the heap, so no reference to its item is left.
+    unsafe { (*top.item.get()).take() }
+  }
+
+  /// Moves all the items of `other` into this heap, in O(1).
+  ///
+  /// Handles to the items of `other` become handles to items of this heap.
+  pub fn meld(&mut self, mut other: Self) {
+    other.owner.meld_into(&self.owner);
+    self.roots.append(&mut other.roots);
+    if let Some(top) = other.top.take() {
+      if self.top.as_ref().map(|t| self.less(t, &top)) != Some(false) {
+        self.top = Some(top);
+      }
+    }
+    self.len += other.len;
+  }
+
+  pub fn contains(&self, handle: &FibonacciHandle<T>) -> bool {
+    self.node(handle).is_some()
+  }
+
+  pub fn get(&self, handle: &FibonacciHandle<T>) -> Option<&T> {
+    // The node is kept alive by the heap until it is removed through `&mut self`.
+    self.node(handle).map(|node| unsafe { &*(node.item() as *const T) })
+  }
+
+  /// Replaces the item of `handle` with one that is not less according to the
+  /// comparator, returning the previous one, in O(1) amortized.
+  ///
+  /// Returns `None` if the item has been removed.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `item` is less than the current item.
+  pub fn promote(&mut self, handle: &FibonacciHandle<T>, item: T) -> Option<T> {
+    let node = self.node(handle)?;
+    assert!(self.cmp.compare(&item, node.item()) != Ordering::Less, "item demoted");
+    let old = unsafe { (*node.item.get()).replace(item) }?;
+    let parent = node.parent.borrow().upgrade();
+    match parent {
+      Some(parent) if self.less(&parent, &node) => self.cut(node),
+      Some(_) => {}
+      None => {
+        if let Some(top) = &self.top {
+          if self.less(top, &node) {
+            self.top = Some(node);
+          }
+        }
+      }
+    }
+    Some(old)
+  }
+
+  /// Removes the item of `handle`, in O(log n) amortized.
+  pub fn remove(&mut self, handle: &FibonacciHandle<T>) -> Option<T> {
+    let node = self.node(handle)?;
+    if node.parent.borrow().upgrade().is_some() {
+      self.cut(node.clone());
+    }
+    self.top = Some(node);
+    self.pop()
+  }
+
+  fn node(&self, handle: &FibonacciHandle<T>) -> Option<Rc<Node<T>>> {
+    let node = handle.0.upgrade()?;
+    let owner = find(&node.owner.borrow());
+    *node.owner.borrow_mut() = owner.clone();
+    if Rc::ptr_eq(&owner, &self.owner) && unsafe { (*node.item.get()).is_some() } {
+      Some(node)
+    } else {
+      None
+    }
+  }
+
+  fn less(&self, a: &Node<T>, b: &Node<T>) -> bool {
+    self.cmp.compare(a.item(), b.item()) == Ordering::Less
+  }
+
+  fn push_root(&mut self, node: Rc<Node<T>>) {
+    if self.top.as_ref().map(|top| self.less(top, &node)) != Some(false) {
+      self.top = Some(node.clone());
+    }
+    self.roots.push_back(node);
+  }
+
+  /// Makes the lesser root a child of the other.
+  fn link(&self, a: Rc<Node<T>>, b: Rc<Node<T>>) -> Rc<Node<T>> {
+    let (parent, child) = if self.less(&a, &b) { (b, a) } else { (a, b) };
+    *child.parent.borrow_mut() = Rc::downgrade(&parent);
+    child.index.set(parent.children.borrow().len());
+    parent.children.borrow_mut().push(child);
+    parent
+  }
+
+  /// Moves a node to the roots, then its ancestors as long as they were already marked.
+  fn cut(&mut self, mut node: Rc<Node<T>>) {
+    loop {
+      let parent = match node.parent.replace(Weak::new()).upgrade() {
+        Some(parent) => parent,
+        None => return,
+      };
+      let index = node.index.get();
+      let mut siblings = parent.children.borrow_mut();
+      siblings.swap_remove(index);
+      if let Some(moved) = siblings.get(index) {
+        moved.index.set(index);
+      }
+      drop(siblings);
+      node.marked.set(false);
+      self.push_root(node);
+      if parent.parent.borrow().upgrade().is_none() || !parent.marked.replace(true) {
+        return;
+      }
+      node = parent;
+    }
+  }
+}
+
+impl<T: Ord> FibonacciHeap<T, MinComparator> {
+  /// Replaces the item of `handle` with a lesser one, in O(1) amortized.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `item` is greater than the current item.
+  pub fn decrease_key(&mut self, handle: &FibonacciHandle<T>, item: T) -> Option<T> {
+    self.promote(handle, item)
+  }
+}
+
+impl<T: Ord> FibonacciHeap<T, MaxComparator> {
+  /// Replaces the item of `handle` with a greater one, in O(1) amortized.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `item` is less than the current item.
+  pub fn increase_key(&mut self, handle: &FibonacciHandle<T>, item: T) -> Option<T> {
+    self.promote(handle, item)
+  }
+}
+
+impl<T> Node<T> {
+  /// Item of a node that is still in a heap.
+  fn item(&self) -> &T {
+    unsafe { (*self.item.get()).as_ref().unwrap() }
+  }
+}
+
+impl<T> Clone for FibonacciHandle<T> {
+  fn clone(&self) -> Self {
+    Self(self.0.clone())
+  }
+}
+
+impl<T, C: Compare<T>> PriorityQueue<T> for FibonacciHeap<T, C> {
+  fn push(&mut self, item: T) {
+    FibonacciHeap::push(self, item);
+  }
+
+  fn pop(&mut self) -> Option<T> {
+    FibonacciHeap::pop(self)
+  }
+
+  fn peek(&self) -> Option<&T> {
+    FibonacciHeap::peek(self)
+  }
+
+  fn len(&self) -> usize {
+    FibonacciHeap::len(self)
+  }
+
+  fn meld(&mut self, other: Self) {
+    FibonacciHeap::meld(self, other)
+  }
+}
+
+impl<T, C: Compare<T> + Default> Default for FibonacciHeap<T, C> {
+  fn default() -> Self {
+    Self::with_comparator(C::default())
+  }
+}
+
+impl<T, C> Drop for FibonacciHeap<T, C> {
+  fn drop(&mut self) {
+    self.top = None;
+    let mut stack = mem::take(&mut self.roots).into_iter().collect::<Vec<_>>();
+    while let Some(node) = stack.pop() {
+      stack.append(&mut node.children.borrow_mut());
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::FibonacciHeap;
+
+  #[test]
+  fn push_pop() {
+    let mut heap = FibonacciHeap::new();
+    for i in (0..100).map(|x| x * 37 % 100) {
+      heap.push(i);
+    }
+    assert_eq!(heap.len(), 100);
+    assert_eq!(heap.peek(), Some(&99));
+    for i in (0..100).rev() {
+      assert_eq!(heap.pop(), Some(i));
+    }
+    assert_eq!(heap.pop(), None);
+  }
+
+  #[test]
+  fn decrease_key() {
+    let mut heap = FibonacciHeap::new_min();
+    let handles = (0..100).map(|i| heap.push(i + 100)).collect::<Vec<_>>();
+    assert_eq!(heap.pop(), Some(100));
+    for (i, handle) in handles.iter().enumerate().skip(1) {
+      let key = if i % 3 == 0 { i as i32 } else { i as i32 + 100 };
+      assert_eq!(heap.decrease_key(handle, key), Some(i as i32 + 100));
+    }
+    let mut expected = (1..100).map(|i| if i % 3 == 0 { i } else { i + 100 }).collect::<Vec<_>>();
+    expected.sort_unstable();
+    assert_eq!(std::iter::from_fn(|| heap.pop()).collect::<Vec<_>>(), expected);
+  }
+
+  #[test]
+  fn cascading_cuts() {
+    let mut heap = FibonacciHeap::new_min();
+    let handles = (0..64).map(|i| heap.push(i * 10)).collect::<Vec<_>>();
+    heap.pop();
+    for (i, handle) in handles.iter().enumerate().rev().step_by(2) {
+      heap.decrease_key(handle, i as i32 * 10 - 5);
+      assert_eq!(heap.get(handle), Some(&(i as i32 * 10 - 5)));
+    }
+    heap.pop();
+    for (i, handle) in handles.iter().enumerate().skip(2).step_by(2) {
+      heap.decrease_key(handle, i as i32 * 10 - 9);
+    }
+    let items = std::iter::from_fn(|| heap.pop()).collect::<Vec<_>>();
+    assert_eq!(items.len(), 62);
+    assert!(items.windows(2).all(|w| w[0] <= w[1]));
+  }
+
+  #[test]
+  #[should_panic]
+  fn promote_demoted() {
+    let mut heap = FibonacciHeap::new();
+    let handle = heap.push(3);
+    heap.increase_key(&handle, 2);
+  }
+
+  #[test]
+  fn meld_handles() {
+    let mut a = FibonacciHeap::new();
+    let mut b = FibonacciHeap::new();
+    let c = FibonacciHeap::<i32>::new();
+    let x = a.push(1);
+    let y = b.push(2);
+    assert!(!a.contains(&y));
+    assert!(!c.contains(&x));
+    a.meld(b);
+    assert!(a.contains(&y));
+    assert_eq!(a.increase_key(&y, 5), Some(2));
+    assert_eq!(a.peek(), Some(&5));
+    let mut d = FibonacciHeap::new();
+    d.meld(a);
+    assert_eq!(d.get(&x), Some(&1));
+    assert_eq!(d.len(), 2);
+  }
+
+  #[test]
+  fn remove() {
+    let mut heap = FibonacciHeap::new_min();
+    let handles = (0..20).map(|i| heap.push(i)).collect::<Vec<_>>();
+    heap.pop();
+    for &i in [7, 0, 19, 3].iter() {
+      let expected = if i == 0 { None } else { Some(i) };
+      assert_eq!(heap.remove(&handles[i as usize]), expected);
+      assert!(!heap.contains(&handles[i as usize]));
+    }
+    assert_eq!(heap.len(), 16);
+    let items = std::iter::from_fn(|| heap.pop()).collect::<Vec<_>>();
+    assert_eq!(items, [1, 2, 4, 5, 6, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18]);
+  }
+
+  #[test]
+  fn handles_outlive_items() {
+    let mut heap = FibonacciHeap::new();
+    let handles = (0..10).map(|i| heap.push(i)).collect::<Vec<_>>();
+    let copies = handles.clone();
+    assert_eq!(heap.pop(), Some(9));
+    assert_eq!(heap.remove(&handles[4]), Some(4));
+    assert!(!heap.contains(&copies[9]));
+    assert_eq!(heap.get(&copies[4]), None);
+    assert_eq!(heap.promote(&copies[9], 20), None);
+    assert_eq!(heap.remove(&copies[4]), None);
+    assert_eq!(heap.increase_key(&handles[0], 10), Some(0));
+    assert_eq!(std::iter::from_fn(|| heap.pop()).collect::<Vec<_>>(), [10, 8, 7, 6, 5, 3, 2, 1]);
+  }
+
+  #[test]
+  fn long_meld_chains() {
+    let mut first = FibonacciHeap::new();
+    let handle = first.push(0);
+    let mut heap = first;
+    for i in 1..100_000 {
+      let mut next = FibonacciHeap::new();
+      next.push(i);
+      next.meld(heap);
+      heap = next;
+    }
+    assert_eq!(heap.get(&handle), Some(&0));
+    assert_eq!(heap.len(), 100_000);
+    let mut other = FibonacciHeap::new();
+    for i in 0..100_000 {
+      let mut next = FibonacciHeap::new();
+      other.push(i);
+      next.meld(other);
+      other = next;
+    }
+  }
+
+  #[test]
+  fn deep() {
+    let mut heap = FibonacciHeap::new();
+    for i in 0..100_000 {
+      heap.push(i);
+    }
+    assert_eq!(heap.pop(), Some(99_999));
+  }
+}
@@ -0,0 +1,408 @@
+use std::cmp::Ordering;
+use std::mem;
+use std::sync::atomic::{self, AtomicUsize};
+
+use super::{sift_down, sift_up, Compare, MaxComparator, MinComparator};
+
+/// Indexed Heap
+///
+/// A binary heap whose items can be looked up, reprioritized and removed through
+/// the handle returned when they are pushed.
+///
+/// # Examples
+///
+/// ```
+/// use structures::heap::IndexedHeap;
+///
+/// let mut heap = IndexedHeap::new_min();
+///
+/// let a = heap.push(5);
+/// let b = heap.push(3);
+///
+/// heap.decrease_key(a, 1);
+///
+/// assert_eq!(heap.pop(), Some((a, 1)));
+/// assert_eq!(heap.get(a), None);
+/// assert_eq!(heap.remove(b), Some(3));
+/// ```
+pub struct IndexedHeap<T, C = MaxComparator> {
+  /// Items with the index of their slot.
+  data: Vec<(usize, T)>,
+  slots: Vec<Slot>,
+  /// Indices of the slots of removed items.
+  free: Vec<usize>,
+  /// Identity of the heap, so that handles of other heaps are rejected.
+  id: usize,
+  cmp: C,
+}
+
+/// Handle to an item of an `IndexedHeap`, which stays valid until the item is removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+  heap: usize,
+  index: usize,
+  generation: u64,
+}
+
+/// Position of an item, with the number of times the slot has been reused.
+struct Slot {
+  position: Option<usize>,
+  generation: u64,
+}
+
+impl<T: Ord> IndexedHeap<T> {
+  pub fn new() -> Self {
+    Self::with_comparator(MaxComparator)
+  }
+
+  pub fn new_min() -> IndexedHeap<T, MinComparator> {
+    IndexedHeap::with_comparator(MinComparator)
+  }
+}
+
+impl<T, C: Compare<T>> IndexedHeap<T, C> {
+  pub fn with_comparator(cmp: C) -> Self {
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+    let id = NEXT_ID.fetch_add(1, atomic::Ordering::Relaxed);
+    Self { data: vec![], slots: vec![], free: vec![], id, cmp }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.data.is_empty()
+  }
+
+  pub fn len(&self) -> usize {
+    self.data.len()
+  }
+
+  pub fn peek(&self) -> Option<(Handle, &T)> {
+    self.data.first().map(|(index, item)| (self.handle(*index), item))
+  }
+
+  pub fn push(&mut self, item: T) -> Handle {
+    let position = self.len();
+    let index = match self.free.pop() {
+      Some(index) => index,
+      None => {
+        self.slots.push(Slot { position: None, generation: 0 });
+        self.slots.len() - 1
+      }
+    };
+    self.slots[index].position = Some(position);
+    self.data.push((index, item));
+    self.sift_up(position);
+    self.handle(index)
+  }
+
+  pub fn pop(&mut self) -> Option<(Handle, T)> {
+    let handle = self.peek()?.0;
+    self.remove(handle).map(|item| (handle, item))
+  }
+
+  pub fn contains(&self, handle: Handle) -> bool {
+    self.position(handle).is_some()
+  }
+
+  pub fn get(&self, handle: Handle) -> Option<&T> {
+    self.position(handle).map(|position| &self.data[position].1)
+  }
+
+  /// Removes the item of `handle`, in O(log n).
+  pub fn remove(&mut self, handle: Handle) -> Option<T> {
+    let position = self.position(handle)?;
+    let (index, item) = self.data.swap_remove(position);
+    let slot = &mut self.slots[index];
+    slot.position = None;
+    slot.generation += 1;
+    self.free.push(index);
+    if position < self.len() {
+      self.slots[self.data[position].0].position = Some(position);
+      self.sift_up(position);
+      self.sift_down(position);
+    }
+    Some(item)
+  }
+
+  /// Replaces the item of `handle`, returning the previous one, in O(log n).
+  ///
+  /// Returns `None` if the item has been removed.
+  pub fn change_priority(&mut self, handle: Handle, item: T) -> Option<T> {
+    let position = self.position(handle)?;
+    let old = mem::replace(&mut self.data[position].1, item);
+    self.sift_up(position);
+    self.sift_down(position);
+    Some(old)
+  }
+
+  /// Replaces the item of `handle` with one that is not less according to the
+  /// comparator, returning the previous one, in O(log n).
+  ///
+  /// Returns `None` if the item has been removed.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `item` is less than the current item.
+  pub fn promote(&mut self, handle: Handle, item: T) -> Option<T> {
+    let position = self.position(handle)?;
+    assert!(self.cmp.compare(&item, &self.data[position].1) != Ordering::Less, "item demoted");
+    let old = mem::replace(&mut self.data[position].1, item);
+    self.sift_up(position);
+    Some(old)
+  }
+
+  /// Replaces the item of `handle` with one that is not greater according to the
+  /// comparator, returning the previous one, in O(log n).
+  ///
+  /// Returns `None` if the item has been removed.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `item` is greater than the current item.
+  pub fn demote(&mut self, handle: Handle, item: T) -> Option<T> {
+    let position = self.position(handle)?;
+    assert!(self.cmp.compare(&item, &self.data[position].1) != Ordering::Greater, "item promoted");
+    let old = mem::replace(&mut self.data[position].1, item);
+    self.sift_down(position);
+    Some(old)
+  }
+
+  fn handle(&self, index: usize) -> Handle {
+    Handle {
+      heap: self.id,
+      index,
+      generation: self.slots[index].generation,
+    }
+  }
+
+  fn position(&self, handle: Handle) -> Option<usize> {
+    let slot = self.slots.get(handle.index)?;
+    if handle.heap == self.id && slot.generation == handle.generation {
+      slot.position
+    } else {
+      None
+    }
+  }
+
+  fn sift_up(&mut self, pos: usize) {
+    let (slots, cmp) = (&mut self.slots, &self.cmp);
+    let cmp = |a: &(usize, T), b: &(usize, T)| cmp.compare(&a.1, &b.1);
+    sift_up::<_, 2>(&mut self.data, pos, &cmp, |&(index, _), pos| slots[index].position = Some(pos));
+  }
+
+  fn sift_down(&mut self, pos: usize) {
+    let (slots, cmp) = (&mut self.slots, &self.cmp);
+    let cmp = |a: &(usize, T), b: &(usize, T)| cmp.compare(&a.1, &b.1);
+    sift_down::<_, 2>(&mut self.data, pos, &cmp, |&(index, _), pos| slots[index].position = Some(pos));
+  }
+}
+
+impl<T: Ord> IndexedHeap<T, MinComparator> {
+  /// Replaces the item of `handle` with a lesser one, in O(log n).
+  ///
+  /// # Panics
+  ///
+  /// Panics if `item` is greater than the current item.
+  pub fn decrease_key(&mut self, handle: Handle, item: T) -> Option<T> {
+    self.promote(handle, item)
+  }
+
+  /// Replaces the item of `handle` with a greater one, in O(log n).
+  ///
+  /// # Panics
+  ///
+  /// Panics if `item` is less than the current item.
+  pub fn increase_key(&mut self, handle: Handle, item: T) -> Option<T> {
+    self.demote(handle, item)
+  }
+}
+
+impl<T: Ord> IndexedHeap<T, MaxComparator> {
+  /// Replaces the item of `handle` with a greater one, in O(log n).
+  ///
+  /// # Panics
+  ///
+  /// Panics if `item` is less than the current item.
+  pub fn increase_key(&mut self, handle: Handle, item: T) -> Option<T> {
+    self.promote(handle, item)
+  }
+
+  /// Replaces the item of `handle` with a lesser one, in O(log n).
+  ///
+  /// # Panics
+  ///
+  /// Panics if `item` is greater than the current item.
+  pub fn decrease_key(&mut self, handle: Handle, item: T) -> Option<T> {
+    self.demote(handle, item)
+  }
+}
+
+impl<T, C: Compare<T> + Default> Default for IndexedHeap<T, C> {
+  fn default() -> Self {
+    Self::with_comparator(C::default())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::cmp::Reverse;
+
+  use super::IndexedHeap;
+  use crate::heap::KeyComparator;
+
+  #[test]
+  fn push_pop() {
+    let mut heap = IndexedHeap::new();
+    let handles = (0..10).map(|i| heap.push(i * 7 % 10)).collect::<Vec<_>>();
+    for i in (0..10).rev() {
+      let (handle, item) = heap.pop().unwrap();
+      assert_eq!(item, i);
+      assert_eq!(handle, handles[i * 3 % 10]);
+      assert!(!heap.contains(handle));
+    }
+    assert_eq!(heap.pop(), None);
+  }
+
+  #[test]
+  fn change_priority() {
+    let mut heap = IndexedHeap::new();
+    let handles = (0..10).map(|i| heap.push(i)).collect::<Vec<_>>();
+    assert_eq!(heap.change_priority(handles[2], 20), Some(2));
+    assert_eq!(heap.change_priority(handles[9], -1), Some(9));
+    assert_eq!(heap.increase_key(handles[0], 15), Some(0));
+    assert_eq!(heap.decrease_key(handles[8], -2), Some(8));
+    assert_eq!(heap.get(handles[2]), Some(&20));
+    let items = std::iter::from_fn(|| heap.pop()).map(|(_, item)| item).collect::<Vec<_>>();
+    assert_eq!(items, [20, 15, 7, 6, 5, 4, 3, 1, -1, -2]);
+  }
+
+  #[test]
+  #[should_panic]
+  fn increase_key_decreased() {
+    let mut heap = IndexedHeap::new_min();
+    let handle = heap.push(3);
+    heap.increase_key(handle, 2);
+  }
+
+  #[test]
+  fn comparator() {
+    let mut heap = IndexedHeap::with_comparator(KeyComparator(|&(k, _): &(i32, char)| Reverse(k)));
+    let handles = (0..10).map(|i| heap.push((i * 7 % 10, 'a'))).collect::<Vec<_>>();
+    assert_eq!(heap.promote(handles[3], (-1, 'b')), Some((1, 'a')));
+    assert_eq!(heap.demote(handles[0], (20, 'c')), Some((0, 'a')));
+    assert_eq!(heap.peek().map(|(_, item)| item), Some(&(-1, 'b')));
+    let items = std::iter::from_fn(|| heap.pop()).map(|(_, (k, _))| k).collect::<Vec<_>>();
+    assert_eq!(items, [-1, 2, 3, 4, 5, 6, 7, 8, 9, 20]);
+  }
+
+  #[test]
+  #[should_panic]
+  fn promote_demoted() {
+    let mut heap = IndexedHeap::with_comparator(KeyComparator(|&k: &i32| Reverse(k)));
+    let handle = heap.push(3);
+    heap.promote(handle, 4);
+  }
+
+  #[test]
+  fn remove() {
+    let mut heap = IndexedHeap::new_min();
+    let handles = (0..10).map(|i| heap.push(i)).collect::<Vec<_>>();
+    for &i in [0, 5, 9, 3].iter() {
+      assert_eq!(heap.remove(handles[i]), Some(i));
+      assert_eq!(heap.remove(handles[i]), None);
+      assert_eq!(heap.get(handles[i]), None);
+    }
+    assert_eq!(heap.len(), 6);
+    let items = std::iter::from_fn(|| heap.pop()).map(|(_, item)| item).collect::<Vec<_>>();
+    assert_eq!(items, [1, 2, 4, 6, 7, 8]);
+  }
+
+  #[test]
+  fn stale_handles() {
+    let mut heap = IndexedHeap::new();
+    let a = heap.push(1);
+    heap.remove(a);
+    let b = heap.push(2);
+    assert_ne!(a, b);
+    assert_eq!(heap.get(a), None);
+    assert_eq!(heap.change_priority(a, 3), None);
+    assert_eq!(heap.get(b), Some(&2));
+  }
+
+  #[test]
+  fn foreign_handles() {
+    let mut a = IndexedHeap::new();
+    let mut b = IndexedHeap::new();
+    let x = a.push(1);
+    let y = b.push(2);
+    assert!(!a.contains(y));
+    assert_eq!(a.get(y), None);
+    assert_eq!(a.change_priority(y, 3), None);
+    assert_eq!(b.remove(x), None);
+    assert_eq!(a.pop(), Some((x, 1)));
+    assert_eq!(b.pop(), Some((y, 2)));
+  }
+
+  #[test]
+  fn reused_slots() {
+    let mut heap = IndexedHeap::new();
+    let first = heap.push(0);
+    let mut previous = first;
+    for i in 1..100_000 {
+      assert_eq!(heap.remove(previous), Some(i - 1));
+      let handle = heap.push(i);
+      for old in [first, previous] {
+        assert_ne!(old, handle);
+        assert_eq!(heap.get(old), None);
+        assert_eq!(heap.remove(old), None);
+        assert_eq!(heap.change_priority(old, -1), None);
+      }
+      assert_eq!(heap.get(handle), Some(&i));
+      previous = handle;
+    }
+    assert_eq!(heap.pop(), Some((previous, 99_999)));
+    assert!(heap.is_empty());
+  }
+
+  #[test]
+  fn positions() {
+    let mut heap = IndexedHeap::new_min();
+    let mut handles = (0..200).map(|i| (heap.push(i * 37 % 200), i * 37 % 200)).collect::<Vec<_>>();
+    for (i, (handle, item)) in handles.iter_mut().enumerate().step_by(3) {
+      *item = if i % 2 == 0 { *item - 1000 } else { *item + 1000 };
+      heap.change_priority(*handle, *item);
+    }
+    for &(handle, item) in handles.iter() {
+      assert_eq!(heap.get(handle), Some(&item));
+    }
+    for (handle, item) in handles.iter().step_by(5) {
+      assert_eq!(heap.remove(*handle), Some(*item));
+    }
+    let items = std::iter::from_fn(|| heap.pop()).map(|(_, item)| item).collect::<Vec<_>>();
+    assert!(items.windows(2).all(|w| w[0] <= w[1]));
+    assert_eq!(items.len(), 160);
+  }
+
+  #[test]
+  fn dijkstra() {
+    let edges = [(0, 1, 4), (0, 2, 1), (2, 1, 2), (1, 3, 1), (2, 3, 5)];
+    let mut dist = [u32::MAX; 4];
+    let mut heap = IndexedHeap::new_min();
+    let mut handles = [None; 4];
+    dist[0] = 0;
+    handles[0] = Some(heap.push((0, 0)));
+    while let Some((_, (d, u))) = heap.pop() {
+      for &(_, v, w) in edges.iter().filter(|e| e.0 == u) {
+        if d + w < dist[v] {
+          dist[v] = d + w;
+          match handles[v] {
+            Some(handle) if heap.contains(handle) => {
+              heap.decrease_key(handle, (dist[v], v));
+            }
+            _ => handles[v] = Some(heap.push((dist[v], v))),
+          }
+        }
+      }
+    }
+    assert_eq!(dist, [0, 3, 1, 4]);
+  }
+}
@@ -0,0 +1,111 @@
+use std::mem;
+
+use super::{Heap, MinComparator, PeekMut};
+
+/// Iterator merging sorted iterators into one sorted stream
+///
+/// Items that compare equal are yielded in the order of their iterators.
+pub struct KMerge<I: Iterator> {
+  /// Next item of each iterator that is not exhausted, with the index of the iterator.
+  heap: Heap<(I::Item, usize), MinComparator>,
+  iters: Vec<I>,
+}
+
+/// Lazily merges iterators sorted in ascending order, pulling one item ahead from each.
+///
+/// # Examples
+///
+/// ```
+/// use structures::heap::kmerge;
+///
+/// let merged = kmerge(vec![vec![1, 4, 7], vec![2, 5], vec![0, 3, 6, 8]]);
+///
+/// assert_eq!(merged.collect::<Vec<_>>(), [0, 1, 2, 3, 4, 5, 6, 7, 8]);
+/// ```
+pub fn kmerge<I>(iters: I) -> KMerge<<I::Item as IntoIterator>::IntoIter>
+where
+  I: IntoIterator,
+  I::Item: IntoIterator,
+  <I::Item as IntoIterator>::Item: Ord,
+{
+  let mut iters = iters.into_iter().map(IntoIterator::into_iter).collect::<Vec<_>>();
+  let heads = iters.iter_mut().enumerate().filter_map(|(i, iter)| iter.next().map(|item| (item, i)));
+  KMerge { heap: heads.collect(), iters }
+}
+
+impl<I: Iterator> Iterator for KMerge<I>
+where
+  I::Item: Ord,
+{
+  type Item = I::Item;
+
+  fn next(&mut self) -> Option<I::Item> {
+    let mut head = self.heap.peek_mut()?;
+    match self.iters[head.1].next() {
+      Some(item) => Some(mem::replace(&mut head.0, item)),
+      None => Some(PeekMut::pop(head).0),
+    }
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let len = self.heap.len();
+    self
+      .iters
+      .iter()
+      .map(Iterator::size_hint)
+      .fold((len, Some(len)), |(low, high), (l, h)| (low.saturating_add(l), high.and_then(|high| high.checked_add(h?))))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::cmp::Ordering;
+
+  use super::kmerge;
+
+  #[test]
+  fn merge() {
+    let iters = (0..5).map(|i| (0..20).map(move |j| j * 5 + i)).collect::<Vec<_>>();
+    let merged = kmerge(iters);
+    assert_eq!(merged.size_hint(), (100, Some(100)));
+    assert_eq!(merged.collect::<Vec<_>>(), (0..100).collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn stable() {
+    let keys = vec![vec![1, 3, 3], vec![0, 1, 1, 3], vec![], vec![1, 2, 3]];
+    let iters = keys.into_iter().enumerate().map(|(i, keys)| keys.into_iter().map(move |k| Keyed(k, i)));
+    let merged = kmerge(iters).map(|Keyed(k, i)| (k, i)).collect::<Vec<_>>();
+    assert_eq!(merged, [(0, 1), (1, 0), (1, 1), (1, 1), (1, 3), (2, 3), (3, 0), (3, 0), (3, 1), (3, 3)]);
+  }
+
+  #[test]
+  fn empty() {
+    assert_eq!(kmerge(Vec::<Vec<i32>>::new()).next(), None);
+    assert_eq!(kmerge(vec![Vec::<i32>::new(), vec![]]).next(), None);
+  }
+
+  /// Item compared by its key only, tagged with the index of its source.
+  #[derive(Debug)]
+  struct Keyed(i32, usize);
+
+  impl Ord for Keyed {
+    fn cmp(&self, other: &Self) -> Ordering {
+      self.0.cmp(&other.0)
+    }
+  }
+
+  impl PartialOrd for Keyed {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+      Some(self.cmp(other))
+    }
+  }
+
+  impl PartialEq for Keyed {
+    fn eq(&self, other: &Self) -> bool {
+      self.0 == other.0
+    }
+  }
+
+  impl Eq for Keyed {}
+}
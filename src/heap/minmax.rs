@@ -0,0 +1,275 @@
+use std::cmp::Ordering;
+
+use super::PriorityQueue;
+
+/// Min-Max Heap
+///
+/// A double-ended priority queue, whose even levels are ordered as a min-heap and
+/// odd levels as a max-heap, so both the least and the greatest items are at the top.
+///
+/// # Examples
+///
+/// ```
+/// use structures::heap::MinMaxHeap;
+///
+/// let mut heap = MinMaxHeap::from(vec![3, 1, 4, 1, 5]);
+///
+/// heap.push(9);
+///
+/// assert_eq!(heap.peek_min(), Some(&1));
+/// assert_eq!(heap.peek_max(), Some(&9));
+/// assert_eq!(heap.pop_max(), Some(9));
+/// assert_eq!(heap.pop_min(), Some(1));
+/// assert_eq!(heap.into_sorted_vec(), [1, 3, 4, 5]);
+/// ```
+pub struct MinMaxHeap<T> {
+  data: Vec<T>,
+}
+
+impl<T: Ord> MinMaxHeap<T> {
+  pub fn new() -> Self {
+    Self { data: Vec::new() }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.data.is_empty()
+  }
+
+  pub fn len(&self) -> usize {
+    self.data.len()
+  }
+
+  pub fn peek_min(&self) -> Option<&T> {
+    self.data.first()
+  }
+
+  pub fn peek_max(&self) -> Option<&T> {
+    self.max_index().map(|i| &self.data[i])
+  }
+
+  pub fn push(&mut self, item: T) {
+    self.data.push(item);
+    self.bubble_up(self.len() - 1);
+  }
+
+  pub fn pop_min(&mut self) -> Option<T> {
+    self.remove(0)
+  }
+
+  pub fn pop_max(&mut self) -> Option<T> {
+    self.max_index().and_then(|i| self.remove(i))
+  }
+
+  /// Moves all the items of `other` into this heap, in O(n + m).
+  pub fn meld(&mut self, mut other: Self) {
+    self.data.append(&mut other.data);
+    self.rebuild();
+  }
+
+  pub fn into_vec(self) -> Vec<T> {
+    self.data
+  }
+
+  /// Items sorted in ascending order.
+  pub fn into_sorted_vec(mut self) -> Vec<T> {
+    let mut vec = Vec::with_capacity(self.len());
+    while let Some(item) = self.pop_min() {
+      vec.push(item);
+    }
+    vec
+  }
+
+  fn max_index(&self) -> Option<usize> {
+    match self.len() {
+      0 => None,
+      1 => Some(0),
+      2 => Some(1),
+      _ => Some(if self.data[1] < self.data[2] { 2 } else { 1 }),
+    }
+  }
+
+  fn remove(&mut self, pos: usize) -> Option<T> {
+    if pos >= self.len() {
+      return None;
+    }
+    let item = self.data.swap_remove(pos);
+    if pos < self.len() {
+      self.trickle_down(pos);
+    }
+    Some(item)
+  }
+
+  fn rebuild(&mut self) {
+    for i in (0..(self.len() / 2)).rev() {
+      self.trickle_down(i);
+    }
+  }
+
+  /// Ordering that items of the level of `pos` have towards their descendants.
+  fn level_order(pos: usize) -> Ordering {
+    if (pos + 1).ilog2() & 1 == 0 {
+      Ordering::Less
+    } else {
+      Ordering::Greater
+    }
+  }
+
+  fn bubble_up(&mut self, pos: usize) {
+    if pos == 0 {
+      return;
+    }
+    let parent = (pos - 1) / 2;
+    let order = Self::level_order(pos);
+    if self.data[parent].cmp(&self.data[pos]) == order {
+      self.data.swap(pos, parent);
+      self.bubble_up_levels(parent, order.reverse());
+    } else {
+      self.bubble_up_levels(pos, order);
+    }
+  }
+
+  /// Moves an item up through the grandparents, which are on the same kind of level.
+  fn bubble_up_levels(&mut self, mut pos: usize, order: Ordering) {
+    while pos > 2 {
+      let grandparent = (pos - 3) / 4;
+      if self.data[pos].cmp(&self.data[grandparent]) != order {
+        break;
+      }
+      self.data.swap(pos, grandparent);
+      pos = grandparent;
+    }
+  }
+
+  fn trickle_down(&mut self, mut pos: usize) {
+    let order = Self::level_order(pos);
+    loop {
+      let children = (pos * 2 + 1..=pos * 2 + 2).chain(pos * 4 + 3..=pos * 4 + 6).filter(|&i| i < self.len());
+      let next = match children.reduce(|a, b| if self.data[b].cmp(&self.data[a]) == order { b } else { a }) {
+        Some(next) if self.data[next].cmp(&self.data[pos]) == order => next,
+        _ => return,
+      };
+      self.data.swap(pos, next);
+      if next <= pos * 2 + 2 {
+        return;
+      }
+      let parent = (next - 1) / 2;
+      if self.data[parent].cmp(&self.data[next]) == order {
+        self.data.swap(next, parent);
+      }
+      pos = next;
+    }
+  }
+}
+
+impl<T: Ord> PriorityQueue<T> for MinMaxHeap<T> {
+  fn push(&mut self, item: T) {
+    MinMaxHeap::push(self, item)
+  }
+
+  fn pop(&mut self) -> Option<T> {
+    self.pop_max()
+  }
+
+  fn peek(&self) -> Option<&T> {
+    self.peek_max()
+  }
+
+  fn len(&self) -> usize {
+    MinMaxHeap::len(self)
+  }
+
+  fn meld(&mut self, other: Self) {
+    MinMaxHeap::meld(self, other)
+  }
+}
+
+impl<T: Ord> Default for MinMaxHeap<T> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<T: Ord> From<Vec<T>> for MinMaxHeap<T> {
+  /// Builds a heap from the items of `vec` in O(n).
+  fn from(vec: Vec<T>) -> Self {
+    let mut heap = Self { data: vec };
+    heap.rebuild();
+    heap
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::MinMaxHeap;
+
+  #[test]
+  fn push_pop() {
+    let mut heap = MinMaxHeap::new();
+    for i in (0..100).map(|x| x * 37 % 100) {
+      heap.push(i);
+      check(&heap);
+    }
+    for i in 0..50 {
+      assert_eq!(heap.peek_min(), Some(&i));
+      assert_eq!(heap.peek_max(), Some(&(99 - i)));
+      assert_eq!(heap.pop_min(), Some(i));
+      assert_eq!(heap.pop_max(), Some(99 - i));
+      check(&heap);
+    }
+    assert_eq!(heap.pop_min(), None);
+    assert_eq!(heap.pop_max(), None);
+  }
+
+  #[test]
+  fn from_vec() {
+    for n in 0..40 {
+      let vec = (0..n).map(|i| i * 41 % n).collect::<Vec<_>>();
+      let heap = MinMaxHeap::from(vec);
+      check(&heap);
+      assert_eq!(heap.into_sorted_vec(), (0..n).collect::<Vec<_>>());
+    }
+  }
+
+  #[test]
+  fn duplicates() {
+    let mut heap = MinMaxHeap::from(vec![2, 2, 1, 1, 3, 3, 2]);
+    heap.push(1);
+    heap.push(3);
+    check(&heap);
+    assert_eq!(heap.pop_max(), Some(3));
+    assert_eq!(heap.pop_min(), Some(1));
+    assert_eq!(heap.into_sorted_vec(), [1, 1, 2, 2, 2, 3, 3]);
+  }
+
+  #[test]
+  fn top_k() {
+    let mut heap = MinMaxHeap::new();
+    for i in (0..1000).map(|x| x * 389 % 1000) {
+      heap.push(i);
+      if heap.len() > 10 {
+        heap.pop_min();
+      }
+    }
+    assert_eq!(heap.into_sorted_vec(), (990..1000).collect::<Vec<_>>());
+  }
+
+  /// Checks that items on min levels are not greater than their descendants, and
+  /// items on max levels not less.
+  fn check(heap: &MinMaxHeap<i32>) {
+    let data = &heap.data;
+    for i in 1..data.len() {
+      let mut ancestor = (i - 1) / 2;
+      loop {
+        if MinMaxHeap::<i32>::level_order(ancestor).is_lt() {
+          assert!(data[ancestor] <= data[i]);
+        } else {
+          assert!(data[ancestor] >= data[i]);
+        }
+        if ancestor == 0 {
+          break;
+        }
+        ancestor = (ancestor - 1) / 2;
+      }
+    }
+  }
+}
@@ -0,0 +1,46 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Identity of a heap, linked to the heap it has been melded into.
+pub(super) struct Owner {
+  melded_into: RefCell<Option<Rc<Owner>>>,
+}
+
+impl Owner {
+  pub(super) fn new() -> Rc<Self> {
+    Rc::new(Self { melded_into: RefCell::new(None) })
+  }
+
+  pub(super) fn meld_into(&self, owner: &Rc<Owner>) {
+    *self.melded_into.borrow_mut() = Some(owner.clone());
+  }
+}
+
+/// Current owner of a melded heap, compressing the path to it.
+pub(super) fn find(owner: &Rc<Owner>) -> Rc<Owner> {
+  let mut root = owner.clone();
+  loop {
+    let next = root.melded_into.borrow().clone();
+    match next {
+      Some(next) => root = next,
+      None => break,
+    }
+  }
+  let mut owner = owner.clone();
+  while !Rc::ptr_eq(&owner, &root) {
+    let next = owner.melded_into.replace(Some(root.clone()));
+    owner = next.unwrap();
+  }
+  root
+}
+
+impl Drop for Owner {
+  /// Drops the owners melded into one after the other, as recursion could
+  /// overflow the stack on long chains.
+  fn drop(&mut self) {
+    let mut next = self.melded_into.get_mut().take();
+    while let Some(owner) = next {
+      next = Rc::try_unwrap(owner).ok().and_then(|mut owner| owner.melded_into.get_mut().take());
+    }
+  }
+}
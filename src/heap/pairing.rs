@@ -0,0 +1,432 @@
+use std::cell::{RefCell, UnsafeCell};
+use std::cmp::Ordering;
+use std::ptr;
+use std::rc::{Rc, Weak};
+
+use super::owner::{find, Owner};
+use super::{Compare, MaxComparator, MinComparator, PriorityQueue};
+
+/// Pairing Heap
+///
+/// A heap-ordered multiway tree with O(1) `push`, `meld` and key promotion, and
+/// O(log n) amortized `pop`. Handles stay valid across melds.
+///
+/// # Examples
+///
+/// ```
+/// use structures::heap::PairingHeap;
+///
+/// let mut a = PairingHeap::new();
+/// let mut b = PairingHeap::new();
+///
+/// a.push(1);
+/// a.push(4);
+/// let handle = b.push(3);
+///
+/// a.meld(b);
+/// a.increase_key(&handle, 5);
+///
+/// assert_eq!(a.pop(), Some(5));
+/// assert_eq!(a.pop(), Some(4));
+/// assert_eq!(a.pop(), Some(1));
+/// assert_eq!(a.get(&handle), None);
+/// ```
+pub struct PairingHeap<T, C = MaxComparator> {
+  root: Option<Rc<Node<T>>>,
+  len: usize,
+  owner: Rc<Owner>,
+  cmp: C,
+}
+
+/// Handle to an item of a `PairingHeap`, which stays valid until the item is removed
+pub struct PairingHandle<T>(Weak<Node<T>>);
+
+/// Items are only written through `&mut PairingHeap` after checking that the
+/// heap owns the node, so no reference given out by `&self` can be invalidated.
+struct Node<T> {
+  /// Taken out when the item is removed.
+  item: UnsafeCell<Option<T>>,
+  owner: RefCell<Rc<Owner>>,
+  /// Most recently linked child, the first of the list of children.
+  child: RefCell<Option<Rc<Self>>>,
+  /// Next child of the parent, linked before this one.
+  next: RefCell<Option<Rc<Self>>>,
+  /// Previous child of the parent, or the parent itself for the first child.
+  prev: RefCell<Weak<Self>>,
+}
+
+impl<T: Ord> PairingHeap<T> {
+  pub fn new() -> Self {
+    Self::with_comparator(MaxComparator)
+  }
+
+  pub fn new_min() -> PairingHeap<T, MinComparator> {
+    PairingHeap::with_comparator(MinComparator)
+  }
+}
+
+impl<T, C: Compare<T>> PairingHeap<T, C> {
+  pub fn with_comparator(cmp: C) -> Self {
+    Self { root: None, len: 0, owner: Owner::new(), cmp }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn peek(&self) -> Option<&T> {
+    self.root.as_ref().map(|root| root.item())
+  }
+
+  pub fn push(&mut self, item: T) -> PairingHandle<T> {
+    let node = Rc::new(Node {
+      item: UnsafeCell::new(Some(item)),
+      owner: RefCell::new(self.owner.clone()),
+      child: RefCell::new(None),
+      next: RefCell::new(None),
+      prev: RefCell::new(Weak::new()),
+    });
+    let handle = PairingHandle(Rc::downgrade(&node));
+    self.meld_root(node);
+    self.len += 1;
+    handle
+  }
+
+  /// Removes the greatest item, pairing up its children from the most recently
+  /// linked one and then melding the pairs in the opposite direction.
+  pub fn pop(&mut self) -> Option<T> {
+    let root = self.root.take()?;
+    let mut children = vec![];
+    let mut next = root.child.take();
+    while let Some(child) = next {
+      next = child.next.take();
+      *child.prev.borrow_mut() = Weak::new();
+      children.push(child);
+    }
+    let mut children = children.into_iter();
+    let mut pairs = vec![];
+    while let Some(a) = children.next() {
+      pairs.push(match children.next() {
+        Some(b) => self.link(a, b),
+        None => a,
+      });
+    }
+    self.root = pairs.into_iter().rev().reduce(|acc, node| self.link(node, acc));
+    self.len -= 1;
+    // The node is out of the heap, so no reference to its item is left.
+    unsafe { (*root.item.get()).take() }
+  }
+
+  /// Moves all the items of `other` into this heap, in O(1).
+  ///
+  /// Handles to the items of `other` become handles to items of this heap.
+  pub fn meld(&mut self, mut other: Self) {
+    other.owner.meld_into(&self.owner);
+    if let Some(root) = other.root.take() {
+      self.meld_root(root);
+    }
+    self.len += other.len;
+  }
+
+  pub fn contains(&self, handle: &PairingHandle<T>) -> bool {
+    self.node(handle).is_some()
+  }
+
+  pub fn get(&self, handle: &PairingHandle<T>) -> Option<&T> {
+    // The node is kept alive by the heap until it is removed through `&mut self`.
+    self.node(handle).map(|node| unsafe { &*(node.item() as *const T) })
+  }
+
+  /// Replaces the item of `handle` with one that is not less according to the
+  /// comparator, returning the previous one, in O(1).
+  ///
+  /// Returns `None` if the item has been removed.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `item` is less than the current item.
+  pub fn promote(&mut self, handle: &PairingHandle<T>, item: T) -> Option<T> {
+    let node = self.node(handle)?;
+    assert!(self.cmp.compare(&item, node.item()) != Ordering::Less, "item demoted");
+    let old = unsafe { (*node.item.get()).replace(item) }?;
+    if cut(&node) {
+      self.meld_root(node);
+    }
+    Some(old)
+  }
+
+  /// Removes the item of `handle`, in O(log n) amortized.
+  pub fn remove(&mut self, handle: &PairingHandle<T>) -> Option<T> {
+    let node = self.node(handle)?;
+    if !cut(&node) {
+      return self.pop();
+    }
+    let root = self.root.replace(node);
+    let item = self.pop();
+    if let Some(root) = root {
+      self.meld_root(root);
+    }
+    item
+  }
+
+  fn node(&self, handle: &PairingHandle<T>) -> Option<Rc<Node<T>>> {
+    let node = handle.0.upgrade()?;
+    let owner = find(&node.owner.borrow());
+    *node.owner.borrow_mut() = owner.clone();
+    if Rc::ptr_eq(&owner, &self.owner) && unsafe { (*node.item.get()).is_some() } {
+      Some(node)
+    } else {
+      None
+    }
+  }
+
+  fn meld_root(&mut self, node: Rc<Node<T>>) {
+    self.root = Some(match self.root.take() {
+      Some(root) => self.link(root, node),
+      None => node,
+    });
+  }
+
+  /// Makes the lesser root a child of the other.
+  fn link(&self, a: Rc<Node<T>>, b: Rc<Node<T>>) -> Rc<Node<T>> {
+    let (parent, child) = match self.cmp.compare(a.item(), b.item()) {
+      Ordering::Less => (b, a),
+      _ => (a, b),
+    };
+    let next = parent.child.take();
+    if let Some(next) = &next {
+      *next.prev.borrow_mut() = Rc::downgrade(&child);
+    }
+    *child.prev.borrow_mut() = Rc::downgrade(&parent);
+    *child.next.borrow_mut() = next;
+    *parent.child.borrow_mut() = Some(child);
+    parent
+  }
+}
+
+/// Detaches a node from its parent, keeping the order of its siblings, and returns
+/// `false` if it is the root.
+fn cut<T>(node: &Node<T>) -> bool {
+  let prev = match node.prev.replace(Weak::new()).upgrade() {
+    Some(prev) => prev,
+    None => return false,
+  };
+  let next = node.next.take();
+  if let Some(next) = &next {
+    *next.prev.borrow_mut() = Rc::downgrade(&prev);
+  }
+  let first = prev.child.borrow().as_deref().is_some_and(|child| ptr::eq(child, node));
+  if first {
+    *prev.child.borrow_mut() = next;
+  } else {
+    *prev.next.borrow_mut() = next;
+  }
+  true
+}
+
+impl<T: Ord> PairingHeap<T, MinComparator> {
+  /// Replaces the item of `handle` with a lesser one, in O(1).
+  ///
+  /// # Panics
+  ///
+  /// Panics if `item` is greater than the current item.
+  pub fn decrease_key(&mut self, handle: &PairingHandle<T>, item: T) -> Option<T> {
+    self.promote(handle, item)
+  }
+}
+
+impl<T: Ord> PairingHeap<T, MaxComparator> {
+  /// Replaces the item of `handle` with a greater one, in O(1).
+  ///
+  /// # Panics
+  ///
+  /// Panics if `item` is less than the current item.
+  pub fn increase_key(&mut self, handle: &PairingHandle<T>, item: T) -> Option<T> {
+    self.promote(handle, item)
+  }
+}
+
+impl<T> Node<T> {
+  /// Item of a node that is still in a heap.
+  fn item(&self) -> &T {
+    unsafe { (*self.item.get()).as_ref().unwrap() }
+  }
+}
+
+impl<T> Clone for PairingHandle<T> {
+  fn clone(&self) -> Self {
+    Self(self.0.clone())
+  }
+}
+
+impl<T, C: Compare<T>> PriorityQueue<T> for PairingHeap<T, C> {
+  fn push(&mut self, item: T) {
+    PairingHeap::push(self, item);
+  }
+
+  fn pop(&mut self) -> Option<T> {
+    PairingHeap::pop(self)
+  }
+
+  fn peek(&self) -> Option<&T> {
+    PairingHeap::peek(self)
+  }
+
+  fn len(&self) -> usize {
+    PairingHeap::len(self)
+  }
+
+  fn meld(&mut self, other: Self) {
+    PairingHeap::meld(self, other)
+  }
+}
+
+impl<T, C: Compare<T> + Default> Default for PairingHeap<T, C> {
+  fn default() -> Self {
+    Self::with_comparator(C::default())
+  }
+}
+
+impl<T, C> Drop for PairingHeap<T, C> {
+  fn drop(&mut self) {
+    let mut stack = self.root.take().into_iter().collect::<Vec<_>>();
+    while let Some(node) = stack.pop() {
+      stack.extend(node.child.take());
+      stack.extend(node.next.take());
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::PairingHeap;
+
+  #[test]
+  fn push_pop() {
+    let mut heap = PairingHeap::new();
+    for i in (0..100).map(|x| x * 37 % 100) {
+      heap.push(i);
+    }
+    assert_eq!(heap.len(), 100);
+    assert_eq!(heap.peek(), Some(&99));
+    for i in (0..100).rev() {
+      assert_eq!(heap.pop(), Some(i));
+    }
+    assert_eq!(heap.pop(), None);
+  }
+
+  #[test]
+  fn meld() {
+    let mut a = PairingHeap::new_min();
+    let mut b = PairingHeap::new_min();
+    for i in 0..10 {
+      a.push(i * 2);
+      b.push(i * 2 + 1);
+    }
+    a.meld(b);
+    a.meld(PairingHeap::new_min());
+    assert_eq!(a.len(), 20);
+    assert_eq!(std::iter::from_fn(|| a.pop()).collect::<Vec<_>>(), (0..20).collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn decrease_key() {
+    let mut heap = PairingHeap::new_min();
+    let handles = (0..100).map(|i| heap.push(i + 100)).collect::<Vec<_>>();
+    assert_eq!(heap.pop(), Some(100));
+    for (i, handle) in handles.iter().enumerate().skip(1) {
+      let key = if i % 3 == 0 { i as i32 } else { i as i32 + 100 };
+      assert_eq!(heap.decrease_key(handle, key), Some(i as i32 + 100));
+      assert_eq!(heap.get(handle), Some(&key));
+    }
+    let mut expected = (1..100).map(|i| if i % 3 == 0 { i } else { i + 100 }).collect::<Vec<_>>();
+    expected.sort_unstable();
+    assert_eq!(std::iter::from_fn(|| heap.pop()).collect::<Vec<_>>(), expected);
+  }
+
+  #[test]
+  #[should_panic]
+  fn promote_demoted() {
+    let mut heap = PairingHeap::new();
+    let handle = heap.push(3);
+    heap.increase_key(&handle, 2);
+  }
+
+  #[test]
+  fn meld_handles() {
+    let mut a = PairingHeap::new();
+    let mut b = PairingHeap::new();
+    let c = PairingHeap::<i32>::new();
+    let x = a.push(1);
+    let y = b.push(2);
+    assert!(!a.contains(&y));
+    assert!(!c.contains(&x));
+    a.meld(b);
+    assert!(a.contains(&y));
+    assert_eq!(a.increase_key(&y, 5), Some(2));
+    assert_eq!(a.peek(), Some(&5));
+    let mut d = PairingHeap::new();
+    d.meld(a);
+    assert_eq!(d.get(&x), Some(&1));
+    assert_eq!(d.len(), 2);
+  }
+
+  #[test]
+  fn remove() {
+    let mut heap = PairingHeap::new_min();
+    let handles = (0..20).map(|i| heap.push(i)).collect::<Vec<_>>();
+    heap.pop();
+    for &i in [7, 0, 19, 3, 1].iter() {
+      let expected = if i == 0 { None } else { Some(i) };
+      assert_eq!(heap.remove(&handles[i as usize]), expected);
+      assert!(!heap.contains(&handles[i as usize]));
+    }
+    assert_eq!(heap.len(), 15);
+    let items = std::iter::from_fn(|| heap.pop()).collect::<Vec<_>>();
+    assert_eq!(items, [2, 4, 5, 6, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18]);
+  }
+
+  #[test]
+  fn handles_outlive_items() {
+    let mut heap = PairingHeap::new();
+    let handles = (0..10).map(|i| heap.push(i)).collect::<Vec<_>>();
+    let copies = handles.clone();
+    assert_eq!(heap.pop(), Some(9));
+    assert_eq!(heap.remove(&handles[4]), Some(4));
+    assert!(!heap.contains(&copies[9]));
+    assert_eq!(heap.get(&copies[4]), None);
+    assert_eq!(heap.promote(&copies[9], 20), None);
+    assert_eq!(heap.increase_key(&handles[0], 10), Some(0));
+    assert_eq!(std::iter::from_fn(|| heap.pop()).collect::<Vec<_>>(), [10, 8, 7, 6, 5, 3, 2, 1]);
+  }
+
+  #[test]
+  fn cut_keeps_order() {
+    let mut heap = PairingHeap::new();
+    heap.push(100);
+    let handles = (0..6).map(|i| heap.push(i)).collect::<Vec<_>>();
+    assert_eq!(heap.remove(&handles[3]), Some(3));
+    assert_eq!(heap.remove(&handles[5]), Some(5));
+    assert_eq!(heap.increase_key(&handles[0], 50), Some(0));
+    let mut children = vec![];
+    let mut next = heap.root.as_ref().unwrap().child.borrow().clone();
+    while let Some(node) = next {
+      children.push(*node.item());
+      next = node.next.borrow().clone();
+    }
+    assert_eq!(children, [50, 4, 2, 1]);
+  }
+
+  #[test]
+  fn deep() {
+    let mut heap = PairingHeap::new();
+    for i in 0..100_000 {
+      heap.push(i);
+    }
+    assert_eq!(heap.pop(), Some(99_999));
+  }
+}
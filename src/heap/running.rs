@@ -0,0 +1,281 @@
+use super::{Compare, Heap, MinComparator};
+
+/// Running Quantile
+///
+/// Tracks the `q`-quantile of a changing multiset with a max-heap of the items up
+/// to the quantile and a min-heap of the others, kept balanced on every change.
+/// Removed items are only marked as deleted, and dropped once they reach the top
+/// of their heap or outnumber the remaining items.
+///
+/// The quantile of `n` items is the item of rank `floor(q * (n - 1))`, counting from zero.
+///
+/// # Examples
+///
+/// ```
+/// use structures::heap::RunningQuantile;
+///
+/// let mut p90 = RunningQuantile::new(0.9);
+///
+/// for latency in 1..=100 {
+///   p90.insert(latency);
+/// }
+///
+/// assert_eq!(p90.quantile(), Some(&90));
+///
+/// p90.remove(100);
+///
+/// assert_eq!(p90.quantile(), Some(&89));
+/// ```
+pub struct RunningQuantile<T> {
+  q: f64,
+  lower: Heap<T>,
+  lower_deleted: Heap<T>,
+  upper: Heap<T, MinComparator>,
+  upper_deleted: Heap<T, MinComparator>,
+}
+
+/// Running Median
+///
+/// Tracks the median of a changing multiset, with O(log n) `insert` and `remove`
+/// and O(1) `median`.
+///
+/// # Examples
+///
+/// ```
+/// use structures::heap::RunningMedian;
+///
+/// let mut median = RunningMedian::new();
+///
+/// median.insert(5);
+/// median.insert(1);
+/// median.insert(3);
+///
+/// assert_eq!(median.median(), Some(&3));
+///
+/// median.insert(8);
+///
+/// assert_eq!(median.medians(), Some((&3, &5)));
+///
+/// median.remove(1);
+///
+/// assert_eq!(median.median(), Some(&5));
+/// ```
+pub struct RunningMedian<T>(RunningQuantile<T>);
+
+impl<T: Ord> RunningQuantile<T> {
+  /// # Panics
+  ///
+  /// Panics if `q` is not between 0 and 1.
+  pub fn new(q: f64) -> Self {
+    assert!((0.0..=1.0).contains(&q), "quantile out of range");
+    Self {
+      q,
+      lower: Heap::new(),
+      lower_deleted: Heap::new(),
+      upper: Heap::new_min(),
+      upper_deleted: Heap::new_min(),
+    }
+  }
+
+  pub fn q(&self) -> f64 {
+    self.q
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  pub fn len(&self) -> usize {
+    self.lower_len() + self.upper_len()
+  }
+
+  pub fn quantile(&self) -> Option<&T> {
+    self.lower.peek()
+  }
+
+  pub fn insert(&mut self, item: T) {
+    match self.lower.peek() {
+      Some(top) if item <= *top => self.lower.push(item),
+      _ => self.upper.push(item),
+    }
+    self.rebalance();
+  }
+
+  /// Removes an item, which must have been inserted and not removed yet.
+  ///
+  /// # Panics
+  ///
+  /// Panics if there is no item left.
+  pub fn remove(&mut self, item: T) {
+    assert!(!self.is_empty(), "removed item not inserted");
+    match self.lower.peek() {
+      Some(top) if item <= *top => {
+        self.lower_deleted.push(item);
+        clean(&mut self.lower, &mut self.lower_deleted);
+      }
+      _ => {
+        self.upper_deleted.push(item);
+        clean(&mut self.upper, &mut self.upper_deleted);
+      }
+    }
+    self.rebalance();
+  }
+
+  /// Item right after the quantile.
+  fn next(&self) -> Option<&T> {
+    self.upper.peek()
+  }
+
+  fn lower_len(&self) -> usize {
+    self.lower.len() - self.lower_deleted.len()
+  }
+
+  fn upper_len(&self) -> usize {
+    self.upper.len() - self.upper_deleted.len()
+  }
+
+  /// Moves items between the heaps until the lower one holds the items up to the quantile.
+  fn rebalance(&mut self) {
+    let len = self.len();
+    let target = if len == 0 { 0 } else { (self.q * (len - 1) as f64).floor() as usize + 1 };
+    while self.lower_len() > target {
+      let item = self.lower.pop().unwrap();
+      clean(&mut self.lower, &mut self.lower_deleted);
+      self.upper.push(item);
+    }
+    while self.lower_len() < target {
+      let item = self.upper.pop().unwrap();
+      clean(&mut self.upper, &mut self.upper_deleted);
+      self.lower.push(item);
+    }
+  }
+}
+
+impl<T: Ord> RunningMedian<T> {
+  pub fn new() -> Self {
+    Self(RunningQuantile::new(0.5))
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  pub fn len(&self) -> usize {
+    self.0.len()
+  }
+
+  /// Lower median.
+  pub fn median(&self) -> Option<&T> {
+    self.0.quantile()
+  }
+
+  /// Lower and upper medians, which are the same item when there is an odd number of items.
+  pub fn medians(&self) -> Option<(&T, &T)> {
+    let lower = self.0.quantile()?;
+    let upper = if self.len() % 2 == 1 { lower } else { self.0.next()? };
+    Some((lower, upper))
+  }
+
+  pub fn insert(&mut self, item: T) {
+    self.0.insert(item);
+  }
+
+  /// Removes an item, which must have been inserted and not removed yet.
+  ///
+  /// # Panics
+  ///
+  /// Panics if there is no item left.
+  pub fn remove(&mut self, item: T) {
+    self.0.remove(item);
+  }
+}
+
+impl<T: Ord> Default for RunningMedian<T> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Drops the deleted items from the top of `heap`, or from the whole heap once they
+/// outnumber the remaining items.
+fn clean<T: Ord, C: Compare<T>>(heap: &mut Heap<T, C>, deleted: &mut Heap<T, C>) {
+  while let (Some(top), Some(dead)) = (heap.peek(), deleted.peek()) {
+    if top != dead {
+      break;
+    }
+    heap.pop();
+    deleted.pop();
+  }
+  if deleted.len() * 2 > heap.len() {
+    let mut items = heap.drain().collect::<Vec<_>>();
+    let mut dead = deleted.drain().collect::<Vec<_>>();
+    items.sort_unstable();
+    dead.sort_unstable();
+    let mut dead = dead.into_iter().peekable();
+    heap.extend(items.into_iter().filter(|item| dead.next_if_eq(item).is_none()));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{RunningMedian, RunningQuantile};
+
+  #[test]
+  fn median() {
+    let mut median = RunningMedian::new();
+    assert_eq!(median.median(), None);
+    assert_eq!(median.medians(), None);
+    let items = (0..101).map(|i| i * 37 % 101).collect::<Vec<_>>();
+    for (n, &item) in items.iter().enumerate() {
+      median.insert(item);
+      let mut sorted = items[..=n].to_vec();
+      sorted.sort_unstable();
+      assert_eq!(median.medians(), Some((&sorted[n / 2], &sorted[n.div_ceil(2)])));
+    }
+    assert_eq!(median.len(), 101);
+    assert_eq!(median.median(), Some(&50));
+  }
+
+  #[test]
+  fn sliding_window() {
+    let items = (0..500).map(|i| i * 7919 % 1000 / 3).collect::<Vec<_>>();
+    let mut median = RunningMedian::new();
+    for (i, &item) in items.iter().enumerate() {
+      median.insert(item);
+      if i >= 25 {
+        median.remove(items[i - 25]);
+      }
+      let mut window = items[i.saturating_sub(24)..=i].to_vec();
+      window.sort_unstable();
+      assert_eq!(median.len(), window.len());
+      assert_eq!(median.median(), Some(&window[(window.len() - 1) / 2]));
+    }
+    for &item in items[475..].iter() {
+      median.remove(item);
+    }
+    assert!(median.is_empty());
+    assert_eq!(median.median(), None);
+    assert_eq!(median.0.lower.len() + median.0.upper.len(), 0);
+  }
+
+  #[test]
+  fn quantiles() {
+    for &q in [0.0, 0.1, 0.25, 0.9, 0.99, 1.0].iter() {
+      let mut quantile = RunningQuantile::new(q);
+      for i in (0..200).map(|i| i * 13 % 200) {
+        quantile.insert(i);
+      }
+      assert_eq!(quantile.quantile(), Some(&((q * 199.0) as i32)));
+      for i in (0..200).step_by(2) {
+        quantile.remove(i);
+      }
+      assert_eq!(quantile.quantile(), Some(&((q * 99.0) as i32 * 2 + 1)));
+    }
+  }
+
+  #[test]
+  #[should_panic]
+  fn quantile_out_of_range() {
+    RunningQuantile::<i32>::new(1.5);
+  }
+}
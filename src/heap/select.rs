@@ -0,0 +1,112 @@
+use super::{Heap, PeekMut};
+
+/// The `k` greatest items of `iter`, in descending order.
+///
+/// Keeps a min-heap of at most `k` items, in O(n log k) time and O(k) space.
+///
+/// # Examples
+///
+/// ```
+/// use structures::heap::top_k;
+///
+/// assert_eq!(top_k(vec![4, 1, 5, 9, 2, 6], 3), [9, 6, 5]);
+/// ```
+pub fn top_k<T: Ord, I: IntoIterator<Item = T>>(iter: I, k: usize) -> Vec<T> {
+  let mut heap = Heap::new_min();
+  for item in iter {
+    if heap.len() < k {
+      heap.push(item);
+    } else if let Some(mut least) = heap.peek_mut() {
+      if item > *least {
+        *least = item;
+      }
+    }
+  }
+  heap.into_sorted_vec()
+}
+
+/// The `k` least items of `iter`, in ascending order.
+///
+/// Keeps a max-heap of at most `k` items, in O(n log k) time and O(k) space.
+///
+/// # Examples
+///
+/// ```
+/// use structures::heap::bottom_k;
+///
+/// assert_eq!(bottom_k(vec![4, 1, 5, 9, 2, 6], 3), [1, 2, 4]);
+/// ```
+pub fn bottom_k<T: Ord, I: IntoIterator<Item = T>>(iter: I, k: usize) -> Vec<T> {
+  let mut heap = Heap::new();
+  for item in iter {
+    if heap.len() < k {
+      heap.push(item);
+    } else if let Some(mut greatest) = heap.peek_mut() {
+      if item < *greatest {
+        *greatest = item;
+      }
+    }
+  }
+  heap.into_sorted_vec()
+}
+
+/// The `n`-th greatest item of `iter`, counting from zero, in O(m log n).
+///
+/// # Examples
+///
+/// ```
+/// use structures::heap::nth_largest;
+///
+/// assert_eq!(nth_largest(vec![4, 1, 5, 9, 2, 6], 0), Some(9));
+/// assert_eq!(nth_largest(vec![4, 1, 5, 9, 2, 6], 2), Some(5));
+/// assert_eq!(nth_largest(vec![4, 1, 5], 3), None);
+/// ```
+pub fn nth_largest<T: Ord, I: IntoIterator<Item = T>>(iter: I, n: usize) -> Option<T> {
+  let mut heap = Heap::new_min();
+  for item in iter {
+    if heap.len() <= n {
+      heap.push(item);
+    } else if let Some(mut least) = heap.peek_mut() {
+      if item > *least {
+        *least = item;
+      }
+    }
+  }
+  if heap.len() > n {
+    heap.peek_mut().map(PeekMut::pop)
+  } else {
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{bottom_k, nth_largest, top_k};
+
+  #[test]
+  fn top_bottom_k() {
+    let items = (0..1000).map(|i| i * 389 % 1000).collect::<Vec<_>>();
+    assert_eq!(top_k(items.iter().cloned(), 5), [999, 998, 997, 996, 995]);
+    assert_eq!(bottom_k(items.iter().cloned(), 5), [0, 1, 2, 3, 4]);
+    assert_eq!(top_k(items.iter().cloned(), 0), []);
+    assert_eq!(bottom_k(vec![2, 1], 5), [1, 2]);
+  }
+
+  #[test]
+  fn duplicates() {
+    let items = vec![3, 1, 3, 2, 1, 3];
+    assert_eq!(top_k(items.clone(), 4), [3, 3, 3, 2]);
+    assert_eq!(bottom_k(items.clone(), 3), [1, 1, 2]);
+    assert_eq!(nth_largest(items, 3), Some(2));
+  }
+
+  #[test]
+  fn nth() {
+    let items = (0..100).rev().collect::<Vec<_>>();
+    for n in 0..100 {
+      assert_eq!(nth_largest(items.iter(), n), Some(&(99 - n)));
+    }
+    assert_eq!(nth_largest(items, 100), None);
+    assert_eq!(nth_largest(Vec::<i32>::new(), 0), None);
+  }
+}
@@ -0,0 +1,389 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{Deref, DerefMut};
+use std::vec;
+
+use super::{Compare, Heap, MaxComparator, MinComparator, PeekMut, PriorityQueue};
+
+/// Stable Priority Queue
+///
+/// A heap of items with priorities, which pops items of equal priority in the
+/// order they were pushed. Insertion sequence numbers wrap around, and ties are
+/// broken correctly as long as no item stays queued while 2^63 others are pushed.
+///
+/// # Examples
+///
+/// ```
+/// use structures::heap::StablePriorityQueue;
+///
+/// let mut queue = StablePriorityQueue::new();
+///
+/// queue.push_with_priority(1, "a");
+/// queue.push_with_priority(2, "b");
+/// queue.push_with_priority(1, "c");
+/// queue.push((2, "d"));
+///
+/// assert_eq!(queue.pop(), Some((2, "b")));
+/// assert_eq!(queue.pop(), Some((2, "d")));
+/// assert_eq!(queue.pop(), Some((1, "a")));
+/// assert_eq!(queue.pop(), Some((1, "c")));
+/// ```
+#[derive(Clone)]
+pub struct StablePriorityQueue<P, T, C = MaxComparator> {
+  heap: Heap<Entry<P, T>, Stable<C>>,
+  /// Sequence number of the next pushed item.
+  seq: u64,
+}
+
+/// Mutable reference to the first item of a queue with the greatest priority, which
+/// keeps its place among items of equal priority when the guard is dropped
+pub struct StablePeekMut<'a, P, T, C: Compare<P>> {
+  peek: PeekMut<'a, Entry<P, T>, 2, Stable<C>>,
+}
+
+/// Iterator moving the items of a queue out with their priorities, in arbitrary order
+pub struct StableIntoIter<P, T>(vec::IntoIter<Entry<P, T>>);
+
+#[derive(Clone)]
+struct Entry<P, T> {
+  seq: u64,
+  pair: (P, T),
+}
+
+/// Order of the priorities, then insertion order.
+#[derive(Clone)]
+struct Stable<C>(C);
+
+impl<P, T, C: Compare<P>> Compare<Entry<P, T>> for Stable<C> {
+  fn compare(&self, a: &Entry<P, T>, b: &Entry<P, T>) -> Ordering {
+    // Earlier items are greater, comparing sequence numbers modulo 2^64.
+    let earlier = || (b.seq.wrapping_sub(a.seq) as i64).cmp(&0);
+    self.0.compare(&a.pair.0, &b.pair.0).then_with(earlier)
+  }
+}
+
+impl<P: Ord, T> StablePriorityQueue<P, T> {
+  pub fn new() -> Self {
+    Self::with_comparator(MaxComparator)
+  }
+
+  pub fn new_min() -> StablePriorityQueue<P, T, MinComparator> {
+    StablePriorityQueue::with_comparator(MinComparator)
+  }
+
+  pub fn with_capacity(capacity: usize) -> Self {
+    Self {
+      heap: Heap::from_vec_with_comparator(Vec::with_capacity(capacity), Stable(MaxComparator)),
+      seq: 0,
+    }
+  }
+}
+
+impl<P, T, C: Compare<P>> StablePriorityQueue<P, T, C> {
+  /// Creates a queue whose priorities are ordered by `cmp`.
+  pub fn with_comparator(cmp: C) -> Self {
+    Self { heap: Heap::with_comparator(Stable(cmp)), seq: 0 }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.heap.is_empty()
+  }
+
+  pub fn len(&self) -> usize {
+    self.heap.len()
+  }
+
+  pub fn capacity(&self) -> usize {
+    self.heap.capacity()
+  }
+
+  pub fn reserve(&mut self, additional: usize) {
+    self.heap.reserve(additional);
+  }
+
+  pub fn shrink_to_fit(&mut self) {
+    self.heap.shrink_to_fit();
+  }
+
+  /// Item with the greatest priority that was pushed first, with its priority.
+  pub fn peek(&self) -> Option<&(P, T)> {
+    self.heap.peek().map(|entry| &entry.pair)
+  }
+
+  /// Mutable reference to the item `peek` returns, which is moved back after the
+  /// items of its new priority that were pushed before it when the guard is dropped.
+  pub fn peek_mut(&mut self) -> Option<StablePeekMut<'_, P, T, C>> {
+    self.heap.peek_mut().map(|peek| StablePeekMut { peek })
+  }
+
+  /// Iterates the items with their priorities, in arbitrary order.
+  pub fn iter(&self) -> impl Iterator<Item = &(P, T)> {
+    self.heap.iter().map(|entry| &entry.pair)
+  }
+
+  pub fn push(&mut self, pair: (P, T)) {
+    let seq = self.seq;
+    self.seq = self.seq.wrapping_add(1);
+    self.heap.push(Entry { seq, pair });
+  }
+
+  pub fn push_with_priority(&mut self, priority: P, item: T) {
+    self.push((priority, item));
+  }
+
+  pub fn pop(&mut self) -> Option<(P, T)> {
+    self.heap.pop().map(|entry| entry.pair)
+  }
+
+  /// Moves all the items of `other` into this queue, after the items of equal priority of this queue.
+  pub fn append(&mut self, other: &mut Self) {
+    // Shifts the sequence numbers of `other` so that its earliest item comes next.
+    let span = other.heap.iter().map(|entry| other.seq.wrapping_sub(entry.seq)).max().unwrap_or(0);
+    let shift = self.seq.wrapping_add(span).wrapping_sub(other.seq);
+    self.seq = self.seq.wrapping_add(span);
+    let entries = other.heap.drain().map(|entry| Entry { seq: entry.seq.wrapping_add(shift), pair: entry.pair });
+    self.heap.extend(entries);
+  }
+
+  /// Keeps only the items for which `f` returns `true`.
+  pub fn retain<F: FnMut(&(P, T)) -> bool>(&mut self, mut f: F) {
+    self.heap.retain(|entry| f(&entry.pair));
+  }
+
+  pub fn clear(&mut self) {
+    self.heap.clear();
+  }
+
+  /// Removes all the items with their priorities, in arbitrary order.
+  pub fn drain(&mut self) -> impl Iterator<Item = (P, T)> + '_ {
+    self.heap.drain().map(|entry| entry.pair)
+  }
+
+  /// Removes the items with their priorities in pop order, as they are iterated.
+  pub fn drain_sorted(&mut self) -> impl Iterator<Item = (P, T)> + '_ {
+    self.heap.drain_sorted().map(|entry| entry.pair)
+  }
+
+  /// Items with their priorities, in arbitrary order.
+  pub fn into_vec(self) -> Vec<(P, T)> {
+    self.into_iter().collect()
+  }
+
+  /// Items with their priorities, in pop order.
+  pub fn into_sorted_vec(self) -> Vec<(P, T)> {
+    self.heap.into_sorted_vec().into_iter().rev().map(|entry| entry.pair).collect()
+  }
+}
+
+impl<P, T, C: Compare<P>> Deref for StablePeekMut<'_, P, T, C> {
+  type Target = (P, T);
+
+  fn deref(&self) -> &(P, T) {
+    &self.peek.pair
+  }
+}
+
+impl<P, T, C: Compare<P>> DerefMut for StablePeekMut<'_, P, T, C> {
+  fn deref_mut(&mut self) -> &mut (P, T) {
+    &mut self.peek.pair
+  }
+}
+
+impl<P, T, C: Compare<P>> StablePeekMut<'_, P, T, C> {
+  /// Removes the peeked item from the queue.
+  pub fn pop(this: Self) -> (P, T) {
+    PeekMut::pop(this.peek).pair
+  }
+}
+
+impl<P, T> Iterator for StableIntoIter<P, T> {
+  type Item = (P, T);
+
+  fn next(&mut self) -> Option<(P, T)> {
+    self.0.next().map(|entry| entry.pair)
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.0.size_hint()
+  }
+}
+
+impl<P, T> ExactSizeIterator for StableIntoIter<P, T> {}
+
+impl<P, T, C: Compare<P>> PriorityQueue<(P, T)> for StablePriorityQueue<P, T, C> {
+  fn push(&mut self, pair: (P, T)) {
+    StablePriorityQueue::push(self, pair)
+  }
+
+  fn pop(&mut self) -> Option<(P, T)> {
+    StablePriorityQueue::pop(self)
+  }
+
+  fn peek(&self) -> Option<&(P, T)> {
+    StablePriorityQueue::peek(self)
+  }
+
+  fn len(&self) -> usize {
+    StablePriorityQueue::len(self)
+  }
+
+  fn meld(&mut self, mut other: Self) {
+    self.append(&mut other)
+  }
+}
+
+impl<P, T, C: Compare<P> + Default> Default for StablePriorityQueue<P, T, C> {
+  fn default() -> Self {
+    Self::with_comparator(C::default())
+  }
+}
+
+impl<P: Ord, T> From<Vec<(P, T)>> for StablePriorityQueue<P, T> {
+  /// Builds a queue from the items of `vec` with their priorities in O(n), as if
+  /// they were pushed in order.
+  fn from(vec: Vec<(P, T)>) -> Self {
+    let seq = vec.len() as u64;
+    let entries = vec.into_iter().zip(0..).map(|(pair, seq)| Entry { seq, pair }).collect();
+    Self {
+      heap: Heap::from_vec_with_comparator(entries, Stable(MaxComparator)),
+      seq,
+    }
+  }
+}
+
+impl<P, T, C: Compare<P>> Extend<(P, T)> for StablePriorityQueue<P, T, C> {
+  fn extend<I: IntoIterator<Item = (P, T)>>(&mut self, iter: I) {
+    for pair in iter {
+      self.push(pair);
+    }
+  }
+}
+
+impl<P, T, C: Compare<P> + Default> FromIterator<(P, T)> for StablePriorityQueue<P, T, C> {
+  fn from_iter<I: IntoIterator<Item = (P, T)>>(iter: I) -> Self {
+    let mut queue = Self::default();
+    queue.extend(iter);
+    queue
+  }
+}
+
+impl<P, T, C> IntoIterator for StablePriorityQueue<P, T, C> {
+  type Item = (P, T);
+  type IntoIter = StableIntoIter<P, T>;
+
+  /// Iterates the items with their priorities in arbitrary order.
+  fn into_iter(self) -> Self::IntoIter {
+    StableIntoIter(self.heap.into_iter())
+  }
+}
+
+impl<P: fmt::Debug, T: fmt::Debug, C: Compare<P>> fmt::Debug for StablePriorityQueue<P, T, C> {
+  fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+    fmt.debug_list().entries(self.iter()).finish()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{StablePeekMut, StablePriorityQueue};
+
+  #[test]
+  fn fifo_ties() {
+    let mut queue = StablePriorityQueue::new();
+    for i in 0..100 {
+      queue.push_with_priority(i % 3, i);
+    }
+    let items = std::iter::from_fn(|| queue.pop()).collect::<Vec<_>>();
+    let mut expected = (0..100).map(|i| (i % 3, i)).collect::<Vec<_>>();
+    expected.sort_by_key(|&(p, _)| std::cmp::Reverse(p));
+    assert_eq!(items, expected);
+  }
+
+  #[test]
+  fn new_min() {
+    let mut queue = StablePriorityQueue::new_min();
+    queue.extend(vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')]);
+    assert_eq!(queue.peek(), Some(&(1, 'b')));
+    assert_eq!(queue.len(), 4);
+    assert_eq!(queue.into_sorted_vec(), [(1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]);
+  }
+
+  #[test]
+  fn wrapping_sequence() {
+    let mut queue = StablePriorityQueue::new();
+    queue.seq = u64::MAX - 2;
+    for i in 0..6 {
+      queue.push_with_priority(0, i);
+    }
+    assert_eq!(queue.seq, 3);
+    assert_eq!(queue.into_sorted_vec().into_iter().map(|(_, i)| i).collect::<Vec<_>>(), [0, 1, 2, 3, 4, 5]);
+  }
+
+  #[test]
+  fn append() {
+    let mut a = vec![(1, 'a'), (2, 'b')].into_iter().collect::<StablePriorityQueue<_, _>>();
+    let mut b = vec![(1, 'c'), (2, 'd'), (1, 'e')].into_iter().collect::<StablePriorityQueue<_, _>>();
+    a.append(&mut b);
+    assert!(b.is_empty());
+    assert_eq!(a.into_sorted_vec(), [(2, 'b'), (2, 'd'), (1, 'a'), (1, 'c'), (1, 'e')]);
+  }
+
+  #[test]
+  fn append_retags() {
+    let mut a = StablePriorityQueue::new();
+    let mut b = StablePriorityQueue::new();
+    a.seq = u64::MAX - 1;
+    b.seq = 5;
+    for i in 0..4 {
+      a.push_with_priority(i % 2, i);
+      b.push_with_priority(i % 2, i + 10);
+    }
+    b.pop();
+    a.append(&mut b);
+    a.push_with_priority(1, 20);
+    a.push_with_priority(0, 21);
+    let items = a.into_sorted_vec().into_iter().map(|(_, i)| i).collect::<Vec<_>>();
+    assert_eq!(items, [1, 3, 13, 20, 0, 2, 10, 12, 21]);
+  }
+
+  #[test]
+  fn from_vec() {
+    let queue = StablePriorityQueue::from(vec![(1, 'a'), (2, 'b'), (1, 'c'), (2, 'd')]);
+    let mut queue = StablePriorityQueue::from(queue.into_vec());
+    queue.push_with_priority(2, 'e');
+    assert_eq!(queue.len(), 5);
+    let mut items = queue.clone().into_iter().collect::<Vec<_>>();
+    items.sort_unstable();
+    assert_eq!(items, [(1, 'a'), (1, 'c'), (2, 'b'), (2, 'd'), (2, 'e')]);
+    assert_eq!(queue.into_sorted_vec()[2], (2, 'e'));
+  }
+
+  #[test]
+  fn peek_mut() {
+    let mut queue = StablePriorityQueue::from(vec![(3, 'a'), (2, 'b'), (1, 'c'), (2, 'd')]);
+    queue.peek_mut().unwrap().0 = 2;
+    assert_eq!(queue.pop(), Some((2, 'a')));
+    queue.peek_mut().unwrap().1 = 'x';
+    assert_eq!(StablePeekMut::pop(queue.peek_mut().unwrap()), (2, 'x'));
+    assert_eq!(queue.peek(), Some(&(2, 'd')));
+    assert!(StablePriorityQueue::<i32, char>::new().peek_mut().is_none());
+  }
+
+  #[test]
+  fn drain_retain() {
+    let mut queue = StablePriorityQueue::with_capacity(10);
+    assert!(queue.capacity() >= 10);
+    queue.extend((0..10).map(|i| (i % 3, i)));
+    queue.retain(|&(_, i)| i != 3 && i != 4);
+    assert_eq!(queue.drain_sorted().take(3).collect::<Vec<_>>(), [(2, 2), (2, 5), (2, 8)]);
+    assert!(queue.is_empty());
+    queue.extend((0..10).map(|i| (i % 3, i)));
+    queue.reserve(100);
+    assert!(queue.capacity() >= 110);
+    let mut items = queue.drain().map(|(_, i)| i).collect::<Vec<_>>();
+    items.sort_unstable();
+    assert_eq!(items, (0..10).collect::<Vec<_>>());
+    assert!(queue.is_empty());
+  }
+}
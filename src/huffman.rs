@@ -0,0 +1,364 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::hash::Hash;
+
+use crate::heap::Heap;
+use crate::tree::Tree;
+
+/// Huffman Coding
+///
+/// Codes are canonical: they are fully determined by the code length of each
+/// symbol, which is all that needs to be stored to rebuild the coding.
+///
+/// # Examples
+///
+/// ```
+/// use structures::huffman::Huffman;
+///
+/// let huffman = Huffman::from_frequencies(vec![('a', 5), ('b', 2), ('c', 1), ('d', 1)]);
+///
+/// let bits = huffman.encode(&['a', 'b', 'a', 'd']).unwrap();
+///
+/// assert_eq!(bits.len(), 1 + 2 + 1 + 3);
+/// assert_eq!(huffman.decode(&bits), Some(vec!['a', 'b', 'a', 'd']));
+///
+/// let table = Huffman::from_lengths(huffman.lengths().to_vec()).unwrap();
+///
+/// assert_eq!(table.decode(&bits), Some(vec!['a', 'b', 'a', 'd']));
+/// ```
+pub struct Huffman<S> {
+  lengths: Vec<(S, usize)>,
+  codes: HashMap<S, Vec<bool>>,
+  tree: Tree<Option<S>>,
+}
+
+impl<S: Clone + Ord + Hash> Huffman<S> {
+  /// Builds an optimal prefix code from symbol frequencies.
+  ///
+  /// The frequencies of a repeated symbol are added up.
+  pub fn from_frequencies(frequencies: impl IntoIterator<Item = (S, u64)>) -> Self {
+    let mut weights = HashMap::new();
+    let mut symbols = vec![];
+    for (symbol, weight) in frequencies {
+      *weights.entry(symbol.clone()).or_insert_with(|| {
+        symbols.push(symbol);
+        0
+      }) += u128::from(weight);
+    }
+    let leaves = symbols
+      .into_iter()
+      .enumerate()
+      .map(|(order, symbol)| Weighted {
+        weight: weights[&symbol],
+        order,
+        tree: Tree::leaf(Some(symbol)),
+      })
+      .collect::<Vec<_>>();
+    let mut order = leaves.len();
+    let mut heap = Heap::from(leaves);
+
+    while heap.len() > 1 {
+      let x = heap.pop().unwrap();
+      let y = heap.pop().unwrap();
+      let tree = Tree::branch(None, x.tree, y.tree);
+      heap.push(Weighted { weight: x.weight + y.weight, order, tree });
+      order += 1;
+    }
+
+    let mut lengths = vec![];
+    if let Some(root) = heap.pop() {
+      let mut stack = vec![(&root.tree, 0)];
+      while let Some((tree, depth)) = stack.pop() {
+        match tree {
+          Tree::Branch(Some(symbol), _, _) => lengths.push((symbol.clone(), depth.max(1))),
+          Tree::Branch(None, l, r) => {
+            stack.push((r, depth + 1));
+            stack.push((l, depth + 1));
+          }
+          Tree::Empty => {}
+        }
+      }
+    }
+
+    Self::from_lengths(lengths).unwrap()
+  }
+
+  /// Rebuilds the canonical code from the code length of each symbol.
+  ///
+  /// Returns `None` if a length is zero, a symbol is repeated, the lengths cannot
+  /// form a prefix code, or a length is greater than both 64 and the number of
+  /// symbols minus one.
+  pub fn from_lengths(mut lengths: Vec<(S, usize)>) -> Option<Self> {
+    // Bounds the allocations of a corrupt table, while accepting any optimal code,
+    // none of whose codes is longer than the number of symbols minus one.
+    let max = lengths.len().saturating_sub(1).max(MAX_SHORT_LENGTH);
+    if lengths.iter().any(|&(_, length)| length > max) {
+      return None;
+    }
+    lengths.sort_by(|(s1, l1), (s2, l2)| l1.cmp(l2).then_with(|| s1.cmp(s2)));
+
+    let mut codes = HashMap::with_capacity(lengths.len());
+    let mut tree = Tree::Empty;
+    let mut code = vec![];
+    for (i, (symbol, length)) in lengths.iter().enumerate() {
+      if *length == 0 || (i > 0 && !increment(&mut code)) {
+        return None;
+      }
+      code.resize(*length, false);
+      if codes.insert(symbol.clone(), code.clone()).is_some() {
+        return None;
+      }
+      insert(&mut tree, &code, symbol.clone());
+    }
+
+    Some(Self { lengths, codes, tree })
+  }
+
+  /// Symbols and their code lengths, ordered by length and then by symbol.
+  pub fn lengths(&self) -> &[(S, usize)] {
+    &self.lengths
+  }
+
+  pub fn code(&self, symbol: &S) -> Option<&[bool]> {
+    self.codes.get(symbol).map(Vec::as_slice)
+  }
+
+  /// Code tree, with symbols at the leaves and `false` leading to the left.
+  pub fn tree(&self) -> &Tree<Option<S>> {
+    &self.tree
+  }
+
+  /// Returns `None` if a symbol has no code.
+  pub fn encode<'a>(&self, symbols: impl IntoIterator<Item = &'a S>) -> Option<Vec<bool>>
+  where
+    S: 'a,
+  {
+    let mut bits = vec![];
+    for symbol in symbols {
+      bits.extend_from_slice(self.codes.get(symbol)?);
+    }
+    Some(bits)
+  }
+
+  /// Returns `None` if the bits do not form a sequence of codes.
+  pub fn decode(&self, bits: &[bool]) -> Option<Vec<S>> {
+    let mut symbols = vec![];
+    let mut node = &self.tree;
+    for &bit in bits {
+      node = match node {
+        Tree::Branch(None, l, r) => {
+          if bit {
+            r
+          } else {
+            l
+          }
+        }
+        _ => return None,
+      };
+      if let Tree::Branch(Some(symbol), _, _) = node {
+        symbols.push(symbol.clone());
+        node = &self.tree;
+      }
+    }
+    if std::ptr::eq(node, &self.tree) {
+      Some(symbols)
+    } else {
+      None
+    }
+  }
+}
+
+impl Huffman<u8> {
+  /// Serializes the code table as pairs of symbol and code length bytes.
+  ///
+  /// Code lengths fit in a byte, as there are at most 256 symbols.
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(self.lengths.len() * 2);
+    for &(symbol, length) in &self.lengths {
+      bytes.extend_from_slice(&[symbol, u8::try_from(length).unwrap()]);
+    }
+    bytes
+  }
+
+  pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+    let pairs = bytes.chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+      return None;
+    }
+    Self::from_lengths(pairs.map(|pair| (pair[0], pair[1] as usize)).collect())
+  }
+}
+
+/// Code length accepted by `from_lengths` whatever the number of symbols.
+const MAX_SHORT_LENGTH: usize = 64;
+
+/// Heap entry ordered so that the lightest tree, and then the earliest one, is the greatest.
+///
+/// Weights are summed as `u128`, which cannot overflow for any number of `u64` frequencies.
+struct Weighted<S> {
+  weight: u128,
+  order: usize,
+  tree: Tree<Option<S>>,
+}
+
+impl<S> Ord for Weighted<S> {
+  fn cmp(&self, other: &Self) -> Ordering {
+    other.weight.cmp(&self.weight).then(other.order.cmp(&self.order))
+  }
+}
+
+impl<S> PartialOrd for Weighted<S> {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl<S> PartialEq for Weighted<S> {
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl<S> Eq for Weighted<S> {}
+
+/// Increments a binary number in place, returning `false` on overflow.
+fn increment(code: &mut [bool]) -> bool {
+  for bit in code.iter_mut().rev() {
+    *bit = !*bit;
+    if *bit {
+      return true;
+    }
+  }
+  false
+}
+
+fn insert<S>(tree: &mut Tree<Option<S>>, code: &[bool], symbol: S) {
+  let mut node = tree;
+  for &bit in code {
+    if node.is_empty() {
+      *node = Tree::branch(None, Tree::Empty, Tree::Empty);
+    }
+    node = match node {
+      Tree::Branch(_, l, r) => {
+        if bit {
+          r
+        } else {
+          l
+        }
+      }
+      Tree::Empty => unreachable!(),
+    };
+  }
+  *node = Tree::leaf(Some(symbol));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Huffman;
+
+  #[test]
+  fn from_frequencies() {
+    let huffman = new_huffman();
+    let cost = |h: &Huffman<char>| frequencies().iter().map(|(s, w)| w * h.code(s).unwrap().len() as u64).sum::<u64>();
+    assert_eq!(cost(&huffman), 224);
+    assert_eq!(huffman.lengths(), [('a', 1), ('b', 3), ('c', 3), ('d', 3), ('e', 4), ('f', 4)]);
+  }
+
+  #[test]
+  fn canonical() {
+    let huffman = new_huffman();
+    let code = |s| huffman.code(&s).unwrap().iter().map(|&b| if b { '1' } else { '0' }).collect::<String>();
+    assert_eq!(code('a'), "0");
+    assert_eq!(code('b'), "100");
+    assert_eq!(code('c'), "101");
+    assert_eq!(code('d'), "110");
+    assert_eq!(code('e'), "1110");
+    assert_eq!(code('f'), "1111");
+  }
+
+  #[test]
+  fn encode_decode() {
+    let huffman = new_huffman();
+    let text = "deadbeefcafe".chars().collect::<Vec<_>>();
+    let bits = huffman.encode(&text).unwrap();
+    assert_eq!(huffman.decode(&bits), Some(text));
+    assert_eq!(huffman.encode(&['x']), None);
+    assert_eq!(huffman.decode(&[true]), None);
+    assert_eq!(huffman.decode(&[]), Some(vec![]));
+  }
+
+  #[test]
+  fn single_symbol() {
+    let huffman = Huffman::from_frequencies(vec![('a', 3)]);
+    assert_eq!(huffman.code(&'a'), Some(&[false][..]));
+    assert_eq!(huffman.decode(&huffman.encode(&['a', 'a']).unwrap()), Some(vec!['a', 'a']));
+    assert_eq!(huffman.decode(&[true]), None);
+    let huffman = Huffman::<char>::from_frequencies(vec![]);
+    assert_eq!(huffman.encode(&[]), Some(vec![]));
+    assert_eq!(huffman.decode(&[false]), None);
+  }
+
+  #[test]
+  fn from_lengths() {
+    assert!(Huffman::from_lengths(vec![('a', 1), ('b', 1), ('c', 1)]).is_none());
+    assert!(Huffman::from_lengths(vec![('a', 0)]).is_none());
+    assert!(Huffman::from_lengths(vec![('a', 1), ('a', 2)]).is_none());
+    assert!(Huffman::from_lengths(vec![('a', 1), ('b', 2)]).is_some());
+    assert!(Huffman::from_lengths(vec![('a', usize::MAX)]).is_none());
+    assert!(Huffman::from_lengths(vec![('a', 1), ('b', 64)]).is_some());
+    assert!(Huffman::from_lengths(vec![('a', 1), ('b', 65)]).is_none());
+  }
+
+  #[test]
+  fn repeated_symbols() {
+    let huffman = Huffman::from_frequencies(vec![('a', 20), ('b', 13), ('a', 25), ('c', 12)]);
+    let merged = Huffman::from_frequencies(vec![('a', 45), ('b', 13), ('c', 12)]);
+    assert_eq!(huffman.lengths(), merged.lengths());
+    assert_eq!(huffman.lengths(), [('a', 1), ('b', 2), ('c', 2)]);
+  }
+
+  #[test]
+  fn large_weights() {
+    let huffman = Huffman::from_frequencies(vec![('a', u64::MAX), ('b', u64::MAX), ('c', u64::MAX), ('a', 1)]);
+    assert_eq!(huffman.lengths(), [('a', 1), ('b', 2), ('c', 2)]);
+  }
+
+  #[test]
+  fn long_codes() {
+    let mut fibonacci = vec![1u64, 1];
+    for i in 2..90 {
+      fibonacci.push(fibonacci[i - 1] + fibonacci[i - 2]);
+    }
+    let huffman = Huffman::from_frequencies(fibonacci.into_iter().enumerate());
+    assert_eq!(huffman.lengths().last().map(|&(_, length)| length), Some(89));
+    assert!(Huffman::from_lengths(huffman.lengths().to_vec()).is_some());
+  }
+
+  #[test]
+  fn bytes() {
+    let data = b"the quick brown fox jumps over the lazy dog";
+    let mut frequencies = std::collections::HashMap::new();
+    for &b in data.iter() {
+      *frequencies.entry(b).or_insert(0) += 1;
+    }
+    let huffman = Huffman::from_frequencies(frequencies);
+    let bits = huffman.encode(data).unwrap();
+    let table = Huffman::from_bytes(&huffman.to_bytes()).unwrap();
+    assert_eq!(table.lengths(), huffman.lengths());
+    assert_eq!(table.decode(&bits), Some(data.to_vec()));
+    assert!(Huffman::from_bytes(&[1]).is_none());
+    let chain = (0..=255).map(|s| (s, (s as usize + 1).min(255))).collect::<Vec<_>>();
+    let bytes = Huffman::from_lengths(chain).unwrap().to_bytes();
+    assert_eq!(bytes[508..], [254, 255, 255, 255]);
+    assert_eq!(Huffman::from_bytes(&bytes).unwrap().code(&255).map(<[bool]>::len), Some(255));
+  }
+
+  fn new_huffman() -> Huffman<char> {
+    Huffman::from_frequencies(frequencies())
+  }
+
+  fn frequencies() -> Vec<(char, u64)> {
+    vec![('a', 45), ('b', 13), ('c', 12), ('d', 16), ('e', 9), ('f', 5)]
+  }
+}
@@ -1,6 +1,10 @@
+pub mod btree;
 pub mod doublylist;
 pub mod graph;
 pub mod heap;
+pub mod huffman;
 pub mod list;
 pub mod lru;
+pub mod pmap;
 pub mod tree;
+pub mod trie;
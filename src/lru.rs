@@ -97,17 +97,17 @@ mod tests {
   #[test]
   fn new() {
     let cache = LruCache::<(), ()>::with_capacity(2);
-    assert_eq!(cache.is_empty(), true);
+    assert!(cache.is_empty());
     assert_eq!(cache.len(), 0);
   }
 
   #[test]
   fn insert() {
     let mut cache = LruCache::with_capacity(2);
-    assert_eq!(cache.contains(&"k".to_string()), false);
+    assert!(!cache.contains(&"k".to_string()));
     assert_eq!(cache.peek(&"k".to_string()), None);
     assert_eq!(cache.insert("k".to_string(), "v".to_string()), None);
-    assert_eq!(cache.contains(&"k".to_string()), true);
+    assert!(cache.contains(&"k".to_string()));
     assert_eq!(cache.peek(&"k".to_string()), Some(&"v".to_string()));
   }
 
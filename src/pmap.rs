@@ -0,0 +1,346 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::FromIterator;
+use std::rc::Rc;
+
+/// Persistent Map
+///
+/// An ordered map kept as a weight-balanced tree. Updates return a new version
+/// in O(log n), copying only the path to the changed node and sharing every
+/// other subtree with the previous version.
+///
+/// # Examples
+///
+/// ```
+/// use structures::pmap::PersistentMap;
+///
+/// let v1 = PersistentMap::new().insert("x", 1).insert("y", 2);
+/// let v2 = v1.insert("x", 10).remove(&"y");
+///
+/// assert_eq!(v1.get(&"x"), Some(&1));
+/// assert_eq!(v1.get(&"y"), Some(&2));
+/// assert_eq!(v2.get(&"x"), Some(&10));
+/// assert_eq!(v2.get(&"y"), None);
+/// ```
+pub struct PersistentMap<K, V> {
+  root: Link<K, V>,
+}
+
+type Link<K, V> = Option<Rc<Node<K, V>>>;
+
+struct Node<K, V> {
+  key: K,
+  value: V,
+  size: usize,
+  left: Link<K, V>,
+  right: Link<K, V>,
+}
+
+/// A subtree is too heavy when its weight exceeds `DELTA` times its sibling's.
+const DELTA: usize = 3;
+/// A rotation is single when the inner grandchild weighs less than `GAMMA` times the outer one.
+const GAMMA: usize = 2;
+
+impl<K, V> PersistentMap<K, V> {
+  pub fn new() -> Self {
+    Self { root: None }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.root.is_none()
+  }
+
+  pub fn len(&self) -> usize {
+    size(&self.root)
+  }
+
+  /// Iterates the entries in key order.
+  pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+    let mut stack = vec![];
+    let mut link = &self.root;
+    std::iter::from_fn(move || {
+      while let Some(node) = link {
+        stack.push(node);
+        link = &node.left;
+      }
+      let node = stack.pop()?;
+      link = &node.right;
+      Some((&node.key, &node.value))
+    })
+  }
+
+  pub fn first(&self) -> Option<(&K, &V)> {
+    let mut node = self.root.as_ref()?;
+    while let Some(left) = &node.left {
+      node = left;
+    }
+    Some((&node.key, &node.value))
+  }
+
+  pub fn last(&self) -> Option<(&K, &V)> {
+    let mut node = self.root.as_ref()?;
+    while let Some(right) = &node.right {
+      node = right;
+    }
+    Some((&node.key, &node.value))
+  }
+}
+
+impl<K: Ord, V> PersistentMap<K, V> {
+  pub fn get(&self, key: &K) -> Option<&V> {
+    let mut link = &self.root;
+    while let Some(node) = link {
+      link = match key.cmp(&node.key) {
+        Ordering::Less => &node.left,
+        Ordering::Greater => &node.right,
+        Ordering::Equal => return Some(&node.value),
+      };
+    }
+    None
+  }
+
+  pub fn contains_key(&self, key: &K) -> bool {
+    self.get(key).is_some()
+  }
+}
+
+impl<K: Ord + Clone, V: Clone> PersistentMap<K, V> {
+  /// Returns a new version with `key` mapped to `value`.
+  pub fn insert(&self, key: K, value: V) -> Self {
+    Self { root: insert(&self.root, key, value) }
+  }
+
+  /// Returns a new version without `key`, sharing the whole tree if `key` is absent.
+  pub fn remove(&self, key: &K) -> Self {
+    match remove(&self.root, key) {
+      Some(root) => Self { root },
+      None => self.clone(),
+    }
+  }
+}
+
+fn size<K, V>(link: &Link<K, V>) -> usize {
+  link.as_ref().map_or(0, |node| node.size)
+}
+
+fn node<K, V>(key: K, value: V, left: Link<K, V>, right: Link<K, V>) -> Link<K, V> {
+  let size = 1 + size(&left) + size(&right);
+  Some(Rc::new(Node { key, value, size, left, right }))
+}
+
+/// Builds a node whose children were balanced before one of them changed by one entry.
+fn balance<K: Clone, V: Clone>(key: K, value: V, left: Link<K, V>, right: Link<K, V>) -> Link<K, V> {
+  let (wl, wr) = (size(&left) + 1, size(&right) + 1);
+  if wr > DELTA * wl {
+    let r = right.unwrap();
+    let (rl, rr) = (r.left.clone(), r.right.clone());
+    if size(&rl) + 1 < GAMMA * (size(&rr) + 1) {
+      node(r.key.clone(), r.value.clone(), node(key, value, left, rl), rr)
+    } else {
+      let rl = rl.unwrap();
+      let l = node(key, value, left, rl.left.clone());
+      let r = node(r.key.clone(), r.value.clone(), rl.right.clone(), rr);
+      node(rl.key.clone(), rl.value.clone(), l, r)
+    }
+  } else if wl > DELTA * wr {
+    let l = left.unwrap();
+    let (ll, lr) = (l.left.clone(), l.right.clone());
+    if size(&lr) + 1 < GAMMA * (size(&ll) + 1) {
+      node(l.key.clone(), l.value.clone(), ll, node(key, value, lr, right))
+    } else {
+      let lr = lr.unwrap();
+      let l = node(l.key.clone(), l.value.clone(), ll, lr.left.clone());
+      let r = node(key, value, lr.right.clone(), right);
+      node(lr.key.clone(), lr.value.clone(), l, r)
+    }
+  } else {
+    node(key, value, left, right)
+  }
+}
+
+fn insert<K: Ord + Clone, V: Clone>(link: &Link<K, V>, key: K, value: V) -> Link<K, V> {
+  let n = match link {
+    Some(n) => n,
+    None => return node(key, value, None, None),
+  };
+  match key.cmp(&n.key) {
+    Ordering::Less => balance(n.key.clone(), n.value.clone(), insert(&n.left, key, value), n.right.clone()),
+    Ordering::Greater => balance(n.key.clone(), n.value.clone(), n.left.clone(), insert(&n.right, key, value)),
+    Ordering::Equal => node(key, value, n.left.clone(), n.right.clone()),
+  }
+}
+
+/// Returns `None` if `key` is absent.
+fn remove<K: Ord + Clone, V: Clone>(link: &Link<K, V>, key: &K) -> Option<Link<K, V>> {
+  let n = link.as_ref()?;
+  Some(match key.cmp(&n.key) {
+    Ordering::Less => balance(n.key.clone(), n.value.clone(), remove(&n.left, key)?, n.right.clone()),
+    Ordering::Greater => balance(n.key.clone(), n.value.clone(), n.left.clone(), remove(&n.right, key)?),
+    Ordering::Equal => match (&n.left, &n.right) {
+      (None, right) => right.clone(),
+      (left, None) => left.clone(),
+      (left, Some(right)) => {
+        let (key, value, right) = remove_first(right);
+        balance(key, value, left.clone(), right)
+      }
+    },
+  })
+}
+
+fn remove_first<K: Clone, V: Clone>(n: &Node<K, V>) -> (K, V, Link<K, V>) {
+  match &n.left {
+    None => (n.key.clone(), n.value.clone(), n.right.clone()),
+    Some(left) => {
+      let (key, value, left) = remove_first(left);
+      (key, value, balance(n.key.clone(), n.value.clone(), left, n.right.clone()))
+    }
+  }
+}
+
+/// Shares the whole tree.
+impl<K, V> Clone for PersistentMap<K, V> {
+  fn clone(&self) -> Self {
+    Self { root: self.root.clone() }
+  }
+}
+
+impl<K, V> Default for PersistentMap<K, V> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for PersistentMap<K, V> {
+  fn eq(&self, other: &Self) -> bool {
+    self.len() == other.len() && self.iter().eq(other.iter())
+  }
+}
+
+impl<K: Eq, V: Eq> Eq for PersistentMap<K, V> {}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for PersistentMap<K, V> {
+  fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+    fmt.debug_map().entries(self.iter()).finish()
+  }
+}
+
+impl<K: Ord + Clone, V: Clone> FromIterator<(K, V)> for PersistentMap<K, V> {
+  fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+    iter.into_iter().fold(Self::new(), |map, (k, v)| map.insert(k, v))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{size, Link, PersistentMap, DELTA};
+  use std::rc::Rc;
+
+  #[test]
+  fn insert_get() {
+    let map = new_map();
+    assert_eq!(map.len(), 7);
+    assert_eq!(map.get(&3), Some(&30));
+    assert_eq!(map.get(&8), None);
+    assert!(map.contains_key(&1));
+    let map = map.insert(3, 33);
+    assert_eq!(map.len(), 7);
+    assert_eq!(map.get(&3), Some(&33));
+  }
+
+  #[test]
+  fn remove() {
+    let map = new_map();
+    for k in 1..=7 {
+      let removed = map.remove(&k);
+      assert_eq!(removed.len(), 6);
+      assert_eq!(removed.get(&k), None);
+      assert_eq!(removed.iter().map(|(&k, _)| k).collect::<Vec<_>>(), (1..=7).filter(|&x| x != k).collect::<Vec<_>>());
+      check(&removed.root);
+    }
+    let same = map.remove(&8);
+    assert!(Rc::ptr_eq(same.root.as_ref().unwrap(), map.root.as_ref().unwrap()));
+  }
+
+  #[test]
+  fn persistent() {
+    let v1 = new_map();
+    let v2 = v1.insert(8, 80);
+    let v3 = v2.remove(&1);
+    assert_eq!(v1, new_map());
+    assert_eq!(v2.len(), 8);
+    assert_eq!(v3.len(), 7);
+    assert_eq!(v2.get(&1), Some(&10));
+    assert_eq!(v3.get(&1), None);
+    assert_eq!(v3.get(&8), Some(&80));
+  }
+
+  #[test]
+  fn sharing() {
+    let v1 = (0..1024).map(|k| (k, k)).collect::<PersistentMap<_, _>>();
+    let v2 = v1.insert(0, -1);
+    let nodes = |map: &PersistentMap<i32, i32>| {
+      let mut stack = vec![&map.root];
+      let mut nodes = vec![];
+      while let Some(link) = stack.pop() {
+        if let Some(node) = link {
+          nodes.push(Rc::as_ptr(node));
+          stack.push(&node.left);
+          stack.push(&node.right);
+        }
+      }
+      nodes
+    };
+    let (n1, n2) = (nodes(&v1), nodes(&v2));
+    assert_eq!(n1.len(), v1.len());
+    assert_eq!(n2.len(), v2.len());
+    let copied = n2.iter().filter(|n| !n1.contains(n)).count();
+    assert!((1..=2 * 11).contains(&copied), "copied {} nodes", copied);
+  }
+
+  #[test]
+  fn balanced() {
+    let mut map = PersistentMap::new();
+    for k in 0..1000 {
+      map = map.insert(k, ());
+      check(&map.root);
+    }
+    for k in (0..1000).step_by(3) {
+      map = map.remove(&k);
+      check(&map.root);
+    }
+    assert_eq!(map.len(), 666);
+    assert!(map.iter().map(|(k, _)| k).eq((0..1000).filter(|k| k % 3 != 0).collect::<Vec<_>>().iter()));
+  }
+
+  #[test]
+  fn first_last() {
+    assert_eq!(PersistentMap::<i32, i32>::new().first(), None);
+    assert_eq!(new_map().first(), Some((&1, &10)));
+    assert_eq!(new_map().last(), Some((&7, &70)));
+  }
+
+  #[test]
+  fn fmt() {
+    assert_eq!(format!("{:?}", PersistentMap::<i32, i32>::new()), "{}");
+    assert_eq!(format!("{:?}", new_map().remove(&2).remove(&3).remove(&4).remove(&5)), "{1: 10, 6: 60, 7: 70}");
+  }
+
+  /// Checks sizes, key order and weight balance.
+  fn check<V>(link: &Link<i32, V>) -> usize {
+    match link {
+      None => 0,
+      Some(node) => {
+        let (l, r) = (check(&node.left), check(&node.right));
+        assert_eq!(node.size, 1 + l + r);
+        assert!(l + r <= 1 || (l < DELTA * (r + 1) && r < DELTA * (l + 1)));
+        assert!(node.left.iter().all(|n| n.key < node.key));
+        assert!(node.right.iter().all(|n| n.key > node.key));
+        size(link)
+      }
+    }
+  }
+
+  fn new_map() -> PersistentMap<i32, i32> {
+    vec![4, 2, 6, 1, 3, 5, 7].into_iter().map(|k| (k, k * 10)).collect()
+  }
+}
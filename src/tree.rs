@@ -1,3 +1,30 @@
+use std::collections::VecDeque;
+use std::fmt::{self, Write};
+use std::mem::{self, ManuallyDrop};
+use std::ptr;
+
+mod compare;
+mod construct;
+mod merkle;
+mod metrics;
+mod morris;
+mod render;
+mod rose;
+mod splay;
+mod text;
+mod treap;
+mod zipper;
+
+pub use self::compare::Edit;
+pub use self::construct::TraversalError;
+pub use self::merkle::{Digest, MerkleProof, Side};
+pub use self::render::Orientation;
+pub use self::rose::RoseTree;
+pub use self::splay::SplayTree;
+pub use self::text::{ParseTreeError, ParseTreeErrorKind};
+pub use self::treap::Treap;
+pub use self::zipper::TreeZipper;
+
 /// Binary Tree
 ///
 /// # Examples
@@ -11,7 +38,12 @@
 ///
 /// println!("{:?}", tree.iter(TraverseOrder::InOrder).collect::<Vec<_>>());
 /// ```
-#[derive(Debug, PartialEq)]
+///
+/// `Tree` implements `Drop` to free deep trees without recursion, so its fields
+/// cannot be moved out by matching on a `Tree` value. Use [`Tree::into_parts`] instead.
+// The explicit tag leaves the rest of an `Empty` as padding, where Morris traversal
+// stores its threads.
+#[repr(u8)]
 pub enum Tree<T> {
   Empty,
   Branch(T, Box<Self>, Box<Self>),
@@ -33,21 +65,51 @@ impl<T> Tree<T> {
   pub fn left(&self) -> Option<&Self> {
     match self {
       Tree::Empty => None,
-      Tree::Branch(_, l, _) => Some(&l),
+      Tree::Branch(_, l, _) => Some(l),
     }
   }
 
   pub fn right(&self) -> Option<&Self> {
     match self {
       Tree::Empty => None,
-      Tree::Branch(_, _, r) => Some(&r),
+      Tree::Branch(_, _, r) => Some(r),
     }
   }
 
   pub fn value(&self) -> Option<&T> {
     match self {
       Tree::Empty => None,
-      Tree::Branch(v, _, _) => Some(&v),
+      Tree::Branch(v, _, _) => Some(v),
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    match self {
+      Tree::Empty => true,
+      Tree::Branch(..) => false,
+    }
+  }
+
+  /// Moves out the value and the children of a branch.
+  ///
+  /// This replaces matching on an owned `Tree`, which `Drop` rules out:
+  ///
+  /// ```
+  /// use structures::tree::Tree;
+  ///
+  /// let tree = Tree::branch(1, Tree::leaf(2), Tree::empty());
+  ///
+  /// // Was `if let Tree::Branch(v, l, r) = tree { ... }`
+  /// if let Some((v, l, r)) = tree.into_parts() {
+  ///   assert_eq!((v, l, r), (1, Tree::leaf(2), Tree::empty()));
+  /// }
+  /// ```
+  pub fn into_parts(self) -> Option<(T, Self, Self)> {
+    let tree = ManuallyDrop::new(self);
+    match &*tree {
+      Tree::Empty => None,
+      // `tree` is never dropped, so each field is read out exactly once.
+      Tree::Branch(v, l, r) => unsafe { Some((ptr::read(v), *ptr::read(l), *ptr::read(r))) },
     }
   }
 
@@ -84,27 +146,195 @@ impl<T> Tree<T> {
     })
   }
 
+  /// Iterates the values breadth-first, from left to right on each level.
+  pub fn iter_level_order(&self) -> impl Iterator<Item = &T> {
+    let mut queue = VecDeque::from(vec![self]);
+    std::iter::from_fn(move || {
+      while let Some(tree) = queue.pop_front() {
+        if let Tree::Branch(v, l, r) = tree {
+          queue.push_back(l);
+          queue.push_back(r);
+          return Some(v);
+        }
+      }
+      None
+    })
+  }
+
   pub fn traverse(&self, order: TraverseOrder, f: &mut dyn FnMut(&T)) {
-    use {TraverseOrder::*, Tree::*};
-    match self {
-      Empty => {}
-      Branch(v, l, r) => {
-        if let PreOrder = order {
-          f(v)
+    for v in self.iter(order) {
+      f(v)
+    }
+  }
+
+  /// Folds the tree bottom-up without recursion.
+  pub(crate) fn fold<'a, R>(&'a self, mut empty: impl FnMut() -> R, mut branch: impl FnMut(&'a T, R, R) -> R) -> R {
+    enum Task<'a, T> {
+      Fold(&'a Tree<T>),
+      Combine(&'a T),
+    }
+    let mut tasks = vec![Task::Fold(self)];
+    let mut results = vec![];
+    while let Some(task) = tasks.pop() {
+      match task {
+        Task::Fold(Tree::Empty) => results.push(empty()),
+        Task::Fold(Tree::Branch(v, l, r)) => {
+          tasks.push(Task::Combine(v));
+          tasks.push(Task::Fold(r));
+          tasks.push(Task::Fold(l));
         }
-        l.traverse(order, f);
-        if let InOrder = order {
-          f(v)
+        Task::Combine(v) => {
+          let r = results.pop().unwrap();
+          let l = results.pop().unwrap();
+          results.push(branch(v, l, r));
         }
-        r.traverse(order, f);
-        if let PostOrder = order {
-          f(v)
+      }
+    }
+    results.pop().unwrap()
+  }
+
+  /// Consumes the tree, folding it bottom-up without recursion.
+  pub(crate) fn into_fold<R>(self, mut empty: impl FnMut() -> R, mut branch: impl FnMut(T, R, R) -> R) -> R {
+    enum Task<T> {
+      Fold(Tree<T>),
+      Combine(T),
+    }
+    let mut tasks = vec![Task::Fold(self)];
+    let mut results = vec![];
+    while let Some(task) = tasks.pop() {
+      match task {
+        Task::Fold(tree) => match tree.into_parts() {
+          None => results.push(empty()),
+          Some((v, l, r)) => {
+            tasks.push(Task::Combine(v));
+            tasks.push(Task::Fold(r));
+            tasks.push(Task::Fold(l));
+          }
+        },
+        Task::Combine(v) => {
+          let r = results.pop().unwrap();
+          let l = results.pop().unwrap();
+          results.push(branch(v, l, r));
         }
       }
     }
+    results.pop().unwrap()
   }
 }
 
+impl<T> Drop for Tree<T> {
+  /// Detaches the subtrees onto a stack, so that every node is dropped with
+  /// empty children and returns here without allocating.
+  fn drop(&mut self) {
+    let mut stack = vec![];
+    detach_children(self, &mut stack);
+    while let Some(mut tree) = stack.pop() {
+      detach_children(&mut tree, &mut stack);
+    }
+  }
+}
+
+/// Moves the non-empty children of a node onto `stack`, leaving `Empty` in their place.
+fn detach_children<T>(tree: &mut Tree<T>, stack: &mut Vec<Tree<T>>) {
+  if let Tree::Branch(_, l, r) = tree {
+    for child in [l, r] {
+      if !child.is_empty() {
+        stack.push(mem::replace(&mut **child, Tree::Empty));
+      }
+    }
+  }
+}
+
+impl<T: PartialEq> PartialEq for Tree<T> {
+  fn eq(&self, other: &Self) -> bool {
+    let mut stack = vec![(self, other)];
+    while let Some(pair) = stack.pop() {
+      match pair {
+        (Tree::Empty, Tree::Empty) => {}
+        (Tree::Branch(v1, l1, r1), Tree::Branch(v2, l2, r2)) => {
+          if v1 != v2 {
+            return false;
+          }
+          stack.push((r1, r2));
+          stack.push((l1, l2));
+        }
+        _ => return false,
+      }
+    }
+    true
+  }
+}
+
+impl<T: Eq> Eq for Tree<T> {}
+
+/// Same output as a derived `Debug`, written without recursing into subtrees.
+impl<T: fmt::Debug> fmt::Debug for Tree<T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    enum Step<'a, T> {
+      Tree(&'a Tree<T>, usize),
+      Text(&'static str),
+      Indent(usize),
+    }
+
+    let pretty = f.alternate();
+    let mut stack = vec![Step::Tree(self, 0)];
+    while let Some(step) = stack.pop() {
+      match step {
+        Step::Tree(Tree::Empty, _) => f.write_str("Empty")?,
+        Step::Tree(Tree::Branch(v, l, r), depth) if pretty => {
+          f.write_str("Branch(\n")?;
+          write_indent(f, depth + 1)?;
+          write!(Indented { f: &mut *f, depth: depth + 1, on_newline: false }, "{:#?}", v)?;
+          f.write_str(",\n")?;
+          stack.extend([
+            Step::Text(")"),
+            Step::Indent(depth),
+            Step::Text(",\n"),
+            Step::Tree(r, depth + 1),
+            Step::Indent(depth + 1),
+            Step::Text(",\n"),
+            Step::Tree(l, depth + 1),
+            Step::Indent(depth + 1),
+          ]);
+        }
+        Step::Tree(Tree::Branch(v, l, r), depth) => {
+          f.write_str("Branch(")?;
+          v.fmt(f)?;
+          f.write_str(", ")?;
+          stack.extend([Step::Text(")"), Step::Tree(r, depth), Step::Text(", "), Step::Tree(l, depth)]);
+        }
+        Step::Text(text) => f.write_str(text)?,
+        Step::Indent(depth) => write_indent(f, depth)?,
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Writer indenting the lines after the first one, for values in pretty `Debug` output.
+struct Indented<'a, 'b> {
+  f: &'a mut fmt::Formatter<'b>,
+  depth: usize,
+  on_newline: bool,
+}
+
+impl Write for Indented<'_, '_> {
+  fn write_str(&mut self, s: &str) -> fmt::Result {
+    for line in s.split_inclusive('\n') {
+      if self.on_newline && line != "\n" {
+        write_indent(self.f, self.depth)?;
+      }
+      self.on_newline = line.ends_with('\n');
+      self.f.write_str(line)?;
+    }
+    Ok(())
+  }
+}
+
+fn write_indent(f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+  (0..depth).try_for_each(|_| f.write_str("    "))
+}
+
 #[derive(Clone, Copy)]
 pub enum TraverseOrder {
   InOrder,
@@ -143,6 +373,14 @@ mod tests {
     assert_eq!(h(PostOrder), [4, 5, 2, 6, 7, 3, 1]);
   }
 
+  #[test]
+  fn iter_level_order() {
+    assert_eq!(Tree::<()>::empty().iter_level_order().next(), None);
+    assert_eq!(new_tree().iter_level_order().cloned().collect::<Vec<_>>(), [1, 2, 3, 4, 5, 6, 7]);
+    let tree = Tree::branch(1, Tree::branch(2, Tree::empty(), Tree::leaf(4)), Tree::leaf(3));
+    assert_eq!(tree.iter_level_order().cloned().collect::<Vec<_>>(), [1, 2, 3, 4]);
+  }
+
   #[test]
   fn traverse() {
     use super::TraverseOrder::*;
@@ -156,7 +394,55 @@ mod tests {
     assert_eq!(h(PostOrder), [4, 5, 2, 6, 7, 3, 1]);
   }
 
-  fn new_tree() -> Tree<i32> {
+  #[test]
+  fn is_empty() {
+    assert!(Tree::<()>::empty().is_empty());
+    assert!(!Tree::leaf(1).is_empty());
+  }
+
+  #[test]
+  fn into_parts() {
+    assert_eq!(Tree::<()>::empty().into_parts(), None);
+    let (v, l, r) = new_tree().into_parts().unwrap();
+    assert_eq!(v, 1);
+    assert_eq!(l, Tree::branch(2, Tree::leaf(4), Tree::leaf(5)));
+    assert_eq!(r, Tree::branch(3, Tree::leaf(6), Tree::leaf(7)));
+  }
+
+  #[test]
+  fn deep() {
+    use super::TraverseOrder::*;
+    let h = || (0..1_000_000).fold(Tree::empty(), |t, x| Tree::branch(x, Tree::empty(), t));
+    let (t1, t2) = (h(), h());
+    assert!(t1 == t2);
+    assert!(t1 != Tree::branch(0, Tree::empty(), t2));
+    let mut n = 0;
+    t1.traverse(InOrder, &mut |_| n += 1);
+    assert_eq!(n, 1_000_000);
+    let debug = format!("{:?}", t1);
+    assert!(debug.starts_with("Branch(999999, Empty, Branch(999998, Empty, "));
+    assert!(debug.ends_with(&format!("Branch(0, Empty, Empty){}", ")".repeat(999_999))));
+  }
+
+  #[test]
+  fn debug() {
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    enum Derived {
+      Empty,
+      Branch((i32, &'static str), Box<Self>, Box<Self>),
+    }
+
+    let leaf = |v| Derived::Branch(v, Box::new(Derived::Empty), Box::new(Derived::Empty));
+    let derived = Derived::Branch((1, "a\nb"), Box::new(leaf((2, ""))), Box::new(Derived::Empty));
+    let tree = Tree::branch((1, "a\nb"), Tree::leaf((2, "")), Tree::empty());
+    assert_eq!(format!("{:?}", tree), format!("{:?}", derived));
+    assert_eq!(format!("{:#?}", tree), format!("{:#?}", derived));
+    assert_eq!(format!("{:?}", Tree::<i32>::empty()), "Empty");
+    assert_eq!(format!("{:5?}", Tree::leaf(1)), "Branch(    1, Empty, Empty)");
+  }
+
+  pub(super) fn new_tree() -> Tree<i32> {
     Tree::branch(1, Tree::branch(2, Tree::leaf(4), Tree::leaf(5)), Tree::branch(3, Tree::leaf(6), Tree::leaf(7)))
   }
 }
@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::mem;
+
+use super::{Side, Tree};
+
+/// Edit turning one tree into another, at the position given by the sides leading to it
+#[derive(Debug, PartialEq, Eq)]
+pub enum Edit<'a, T> {
+  Insert(Vec<Side>, &'a T),
+  Remove(Vec<Side>, &'a T),
+  Relabel(Vec<Side>, &'a T, &'a T),
+}
+
+impl<T> Tree<T> {
+  /// Swaps the children of every branch.
+  pub fn mirror(&mut self) {
+    let mut stack = vec![self];
+    while let Some(tree) = stack.pop() {
+      if let Tree::Branch(_, l, r) = tree {
+        mem::swap(l, r);
+        stack.push(l);
+        stack.push(r);
+      }
+    }
+  }
+}
+
+impl<T: PartialEq> Tree<T> {
+  /// Whether the tree is its own mirror image.
+  pub fn is_symmetric(&self) -> bool {
+    let mut stack = match self {
+      Tree::Empty => return true,
+      Tree::Branch(_, l, r) => vec![(l, r)],
+    };
+    while let Some(pair) = stack.pop() {
+      match (&**pair.0, &**pair.1) {
+        (Tree::Empty, Tree::Empty) => {}
+        (Tree::Branch(va, la, ra), Tree::Branch(vb, lb, rb)) if va == vb => {
+          stack.push((la, rb));
+          stack.push((ra, lb));
+        }
+        _ => return false,
+      }
+    }
+    true
+  }
+
+  /// Whether the tree equals a subtree of `other`, in O(n + m).
+  pub fn is_subtree_of(&self, other: &Self) -> bool {
+    // Subtrees of the same size are disjoint, so comparing each of them
+    // with `self` visits every node of `other` at most once.
+    let size = self.size();
+    if size == 0 {
+      return true;
+    }
+    let sizes = other.fold(
+      || (0, Tree::Empty),
+      |_, (sl, l), (sr, r)| {
+        let s = 1 + sl + sr;
+        (s, Tree::branch(s, l, r))
+      },
+    );
+    let mut stack = vec![(other, &sizes.1)];
+    while let Some(pair) = stack.pop() {
+      if let (Tree::Branch(_, l, r), Tree::Branch(s, sl, sr)) = pair {
+        if *s == size && pair.0 == self {
+          return true;
+        }
+        if *s > size {
+          stack.push((r, sr));
+          stack.push((l, sl));
+        }
+      }
+    }
+    false
+  }
+
+  /// Edits turning the tree into `other`, in pre-order, matching nodes by position.
+  ///
+  /// ```
+  /// use structures::tree::{Edit::*, Side::*, Tree};
+  ///
+  /// let a = Tree::branch(1, Tree::leaf(2), Tree::empty());
+  /// let b = Tree::branch(1, Tree::leaf(3), Tree::leaf(4));
+  ///
+  /// assert_eq!(a.diff(&b), [Relabel(vec![Left], &2, &3), Insert(vec![Right], &4)]);
+  /// ```
+  pub fn diff<'a>(&'a self, other: &'a Self) -> Vec<Edit<'a, T>> {
+    let mut edits = vec![];
+    let mut path = vec![];
+    let mut stack = vec![(self, other, 0, None)];
+    while let Some((a, b, depth, side)) = stack.pop() {
+      let ((va, la, ra), (vb, lb, rb)) = match (a, b) {
+        (Tree::Empty, Tree::Empty) => continue,
+        (Tree::Branch(va, la, ra), Tree::Branch(vb, lb, rb)) => ((Some(va), &**la, &**ra), (Some(vb), &**lb, &**rb)),
+        (Tree::Branch(va, la, ra), Tree::Empty) => ((Some(va), &**la, &**ra), (None, b, b)),
+        (Tree::Empty, Tree::Branch(vb, lb, rb)) => ((None, a, a), (Some(vb), &**lb, &**rb)),
+      };
+      path.truncate(depth);
+      path.extend(side);
+      match (va, vb) {
+        (Some(va), Some(vb)) if va != vb => edits.push(Edit::Relabel(path.clone(), va, vb)),
+        (Some(va), None) => edits.push(Edit::Remove(path.clone(), va)),
+        (None, Some(vb)) => edits.push(Edit::Insert(path.clone(), vb)),
+        _ => {}
+      }
+      stack.push((ra, rb, path.len(), Some(Side::Right)));
+      stack.push((la, lb, path.len(), Some(Side::Left)));
+    }
+    edits
+  }
+}
+
+impl<T: Eq + Hash> Tree<T> {
+  /// Whether the trees are equal after swapping the children of some branches, in O(n + m).
+  pub fn is_isomorphic(&self, other: &Self) -> bool {
+    // Subtrees are isomorphic when they get the same id, given by their value and the
+    // unordered ids of their children.
+    let mut ids = HashMap::new();
+    let mut id = |v, l: usize, r: usize| {
+      let n = ids.len() + 1;
+      *ids.entry((v, l.min(r), l.max(r))).or_insert(n)
+    };
+    self.fold(|| 0, &mut id) == other.fold(|| 0, &mut id)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Edit::*;
+  use crate::tree::{tests::new_tree, Side::*, Tree};
+
+  #[test]
+  fn mirror() {
+    let mut tree = new_tree();
+    tree.mirror();
+    assert_eq!(tree, parse("1(3(7,6),2(5,4))"));
+    tree.mirror();
+    assert_eq!(tree, new_tree());
+  }
+
+  #[test]
+  fn is_symmetric() {
+    assert!(Tree::<i32>::empty().is_symmetric());
+    assert!(Tree::leaf(1).is_symmetric());
+    assert!(parse("1(2(3,4),2(4,3))").is_symmetric());
+    assert!(!parse("1(2(3,4),2(3,4))").is_symmetric());
+    assert!(!parse("1(2,-)").is_symmetric());
+    assert!(!new_tree().is_symmetric());
+  }
+
+  #[test]
+  fn is_subtree_of() {
+    let tree = new_tree();
+    assert!(Tree::empty().is_subtree_of(&tree));
+    assert!(tree.is_subtree_of(&tree));
+    assert!(parse("3(6,7)").is_subtree_of(&tree));
+    assert!(Tree::leaf(5).is_subtree_of(&tree));
+    assert!(!parse("3(6,-)").is_subtree_of(&tree));
+    assert!(!parse("2(4,5(1,-))").is_subtree_of(&tree));
+    assert!(!Tree::leaf(1).is_subtree_of(&Tree::empty()));
+  }
+
+  #[test]
+  fn is_isomorphic() {
+    let tree = new_tree();
+    assert!(Tree::<i32>::empty().is_isomorphic(&Tree::empty()));
+    assert!(tree.is_isomorphic(&new_tree()));
+    assert!(tree.is_isomorphic(&parse("1(3(7,6),2(4,5))")));
+    assert!(tree.is_isomorphic(&parse("1(2(5,4),3(6,7))")));
+    assert!(!tree.is_isomorphic(&parse("1(2(4,6),3(5,7))")));
+    assert!(parse("1(2,-)").is_isomorphic(&parse("1(-,2)")));
+    assert!(!Tree::leaf(1).is_isomorphic(&Tree::empty()));
+  }
+
+  #[test]
+  fn diff() {
+    let tree = new_tree();
+    assert_eq!(tree.diff(&new_tree()), []);
+    let other = parse("1(2(4,-),8(6,7(9,-)))");
+    assert_eq!(
+      tree.diff(&other),
+      [Remove(vec![Left, Right], &5), Relabel(vec![Right], &3, &8), Insert(vec![Right, Right, Left], &9)]
+    );
+    let other = parse("1(-,3)");
+    assert_eq!(
+      tree.diff(&other),
+      [
+        Remove(vec![Left], &2),
+        Remove(vec![Left, Left], &4),
+        Remove(vec![Left, Right], &5),
+        Remove(vec![Right, Left], &6),
+        Remove(vec![Right, Right], &7)
+      ]
+    );
+    assert_eq!(Tree::empty().diff(&Tree::leaf(1)), [Insert(vec![], &1)]);
+  }
+
+  #[test]
+  fn diff_deep() {
+    let a = (0..100_000).fold(Tree::leaf(-1), |t, x| Tree::branch(x, t, Tree::empty()));
+    let b = (0..100_000).fold(Tree::leaf(-2), |t, x| Tree::branch(x, t, Tree::empty()));
+    let edits = a.diff(&b);
+    assert_eq!(edits.len(), 1);
+    assert!(matches!(&edits[0], Relabel(path, -1, -2) if path.len() == 100_000 && path.iter().all(|&s| s == Left)));
+  }
+
+  fn parse(s: &str) -> Tree<i32> {
+    Tree::parse(s).unwrap()
+  }
+}
@@ -0,0 +1,146 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+
+use super::Tree;
+
+/// Error returned when a tree cannot be rebuilt from its traversal sequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraversalError {
+  /// The sequences have different lengths
+  LengthMismatch,
+  /// A value occurs more than once in a sequence
+  Duplicate,
+  /// The sequences do not describe the same tree
+  Inconsistent,
+}
+
+impl fmt::Display for TraversalError {
+  fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      TraversalError::LengthMismatch => write!(fmt, "traversal sequences have different lengths"),
+      TraversalError::Duplicate => write!(fmt, "traversal sequence contains duplicate values"),
+      TraversalError::Inconsistent => write!(fmt, "traversal sequences are inconsistent"),
+    }
+  }
+}
+
+impl std::error::Error for TraversalError {}
+
+impl<T: Clone + Eq + Hash> Tree<T> {
+  /// Rebuilds a tree from its pre-order and in-order sequences in O(n).
+  ///
+  /// ```
+  /// use structures::tree::{TraverseOrder::*, Tree};
+  ///
+  /// let tree = Tree::branch(1, Tree::leaf(2), Tree::leaf(3));
+  /// let preorder = tree.iter(PreOrder).cloned().collect::<Vec<_>>();
+  /// let inorder = tree.iter(InOrder).cloned().collect::<Vec<_>>();
+  ///
+  /// assert_eq!(Tree::from_preorder_inorder(&preorder, &inorder), Ok(tree));
+  /// ```
+  pub fn from_preorder_inorder(preorder: &[T], inorder: &[T]) -> Result<Self, TraversalError> {
+    Self::from_traversal(preorder, inorder, true)
+  }
+
+  /// Rebuilds a tree from its post-order and in-order sequences in O(n).
+  pub fn from_postorder_inorder(postorder: &[T], inorder: &[T]) -> Result<Self, TraversalError> {
+    Self::from_traversal(postorder, inorder, false)
+  }
+
+  /// `root_first` tells whether `order` is a pre-order (root first) or post-order sequence.
+  fn from_traversal(order: &[T], inorder: &[T], root_first: bool) -> Result<Self, TraversalError> {
+    if order.len() != inorder.len() {
+      return Err(TraversalError::LengthMismatch);
+    }
+
+    let mut seen = HashSet::with_capacity(order.len());
+    if !order.iter().all(|v| seen.insert(v)) {
+      return Err(TraversalError::Duplicate);
+    }
+
+    let mut positions = HashMap::with_capacity(inorder.len());
+    for (i, v) in inorder.iter().enumerate() {
+      if positions.insert(v, i).is_some() {
+        return Err(TraversalError::Duplicate);
+      }
+    }
+
+    enum Task<'a, T> {
+      Build(usize, usize, usize),
+      Assemble(&'a T),
+    }
+
+    let mut tasks = vec![Task::Build(0, 0, order.len())];
+    let mut trees = vec![];
+
+    while let Some(task) = tasks.pop() {
+      match task {
+        Task::Build(_, _, 0) => trees.push(Tree::Empty),
+        Task::Build(start, in_start, len) => {
+          let (root, left_start) =
+            if root_first { (&order[start], start + 1) } else { (&order[start + len - 1], start) };
+          let k = match positions.get(root) {
+            Some(&k) if in_start <= k && k < in_start + len => k,
+            _ => return Err(TraversalError::Inconsistent),
+          };
+          let left_len = k - in_start;
+          tasks.push(Task::Assemble(root));
+          tasks.push(Task::Build(left_start + left_len, k + 1, len - left_len - 1));
+          tasks.push(Task::Build(left_start, in_start, left_len));
+        }
+        Task::Assemble(v) => {
+          let r = trees.pop().unwrap();
+          let l = trees.pop().unwrap();
+          trees.push(Tree::branch(v.clone(), l, r));
+        }
+      }
+    }
+
+    Ok(trees.pop().unwrap())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::tree::{tests::new_tree, TraverseOrder::*, Tree};
+  use super::TraversalError::*;
+
+  #[test]
+  fn from_preorder_inorder() {
+    let tree = new_tree();
+    let preorder = tree.iter(PreOrder).cloned().collect::<Vec<_>>();
+    let inorder = tree.iter(InOrder).cloned().collect::<Vec<_>>();
+    assert_eq!(Tree::from_preorder_inorder(&preorder, &inorder), Ok(tree));
+    assert_eq!(Tree::<i32>::from_preorder_inorder(&[], &[]), Ok(Tree::empty()));
+    let skewed = Tree::branch(1, Tree::branch(2, Tree::empty(), Tree::leaf(3)), Tree::empty());
+    assert_eq!(Tree::from_preorder_inorder(&[1, 2, 3], &[2, 3, 1]), Ok(skewed));
+  }
+
+  #[test]
+  fn from_postorder_inorder() {
+    let tree = new_tree();
+    let postorder = tree.iter(PostOrder).cloned().collect::<Vec<_>>();
+    let inorder = tree.iter(InOrder).cloned().collect::<Vec<_>>();
+    assert_eq!(Tree::from_postorder_inorder(&postorder, &inorder), Ok(tree));
+    assert_eq!(Tree::<i32>::from_postorder_inorder(&[], &[]), Ok(Tree::empty()));
+  }
+
+  #[test]
+  fn errors() {
+    assert_eq!(Tree::from_preorder_inorder(&[1, 2], &[1]), Err(LengthMismatch));
+    assert_eq!(Tree::from_preorder_inorder(&[1, 1], &[1, 2]), Err(Duplicate));
+    assert_eq!(Tree::from_postorder_inorder(&[1, 2], &[2, 2]), Err(Duplicate));
+    assert_eq!(Tree::from_preorder_inorder(&[1, 2], &[1, 3]), Err(Inconsistent));
+    assert_eq!(Tree::from_postorder_inorder(&[3, 1, 2], &[1, 2, 3]), Err(Inconsistent));
+  }
+
+  #[test]
+  fn deep() {
+    let inorder = (0..100_000).collect::<Vec<_>>();
+    let preorder = inorder.clone();
+    let tree = Tree::from_preorder_inorder(&preorder, &inorder).unwrap();
+    assert_eq!(tree.iter(PreOrder).count(), 100_000);
+    assert_eq!(tree.left(), Some(&Tree::empty()));
+  }
+}
@@ -0,0 +1,407 @@
+use std::hash::{Hash, Hasher};
+
+use super::Tree;
+
+/// Merkle digest of a value or subtree
+///
+/// Digests are SHA-256 hashes of what the `Hash` impls of the values write, with
+/// integers in little-endian and `usize`/`isize` widened to 64 bits, so they are
+/// the same on every platform and Rust release as long as those impls are.
+pub type Digest = [u8; 32];
+
+/// Direction taken from a branch to one of its children
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+  Left,
+  Right,
+}
+
+/// Inclusion proof of a value in a tree with a known Merkle root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleProof {
+  /// Digests of the children of the proven node
+  children: (Digest, Digest),
+  /// From the proven node up to the root: the side the node is on, the digest of
+  /// its parent's value and the digest of its sibling
+  steps: Vec<(Side, Digest, Digest)>,
+}
+
+impl MerkleProof {
+  /// Checks that `value` is in the tree whose Merkle root is `root`.
+  pub fn verify<T: Hash>(&self, root: Digest, value: &T) -> bool {
+    let digest = branch_digest(hash(value), self.children.0, self.children.1);
+    let digest = self.steps.iter().fold(digest, |digest, &(side, parent, sibling)| match side {
+      Side::Left => branch_digest(parent, digest, sibling),
+      Side::Right => branch_digest(parent, sibling, digest),
+    });
+    digest == root
+  }
+
+  /// Sides leading from the root to the proven node.
+  pub fn path(&self) -> Vec<Side> {
+    self.steps.iter().rev().map(|&(side, _, _)| side).collect()
+  }
+}
+
+impl<T: Hash> Tree<T> {
+  /// Digest of the whole tree, covering every value and the shape of the tree.
+  ///
+  /// ```
+  /// use structures::tree::Tree;
+  ///
+  /// let tree = Tree::branch(1, Tree::leaf(2), Tree::leaf(3));
+  ///
+  /// assert_eq!(tree.merkle_root(), Tree::branch(1, Tree::leaf(2), Tree::leaf(3)).merkle_root());
+  /// assert_ne!(tree.merkle_root(), Tree::branch(1, Tree::leaf(3), Tree::leaf(2)).merkle_root());
+  ///
+  /// let proof = tree.merkle_proof(&3).unwrap();
+  ///
+  /// assert!(proof.verify(tree.merkle_root(), &3));
+  /// assert!(!proof.verify(tree.merkle_root(), &4));
+  /// ```
+  pub fn merkle_root(&self) -> Digest {
+    self.fold(empty_digest, |v, l, r| branch_digest(hash(v), l, r))
+  }
+
+  /// Digests of every subtree, in a tree of the same shape.
+  pub fn merkle_digests(&self) -> Tree<Digest> {
+    self
+      .fold(
+        || (empty_digest(), Tree::Empty),
+        |v, (dl, l), (dr, r)| {
+          let digest = branch_digest(hash(v), dl, dr);
+          (digest, Tree::branch(digest, l, r))
+        },
+      )
+      .1
+  }
+
+  /// Sides leading to the largest subtrees that differ from a remote tree known only
+  /// by its [`merkle_digests`](Tree::merkle_digests), in pre-order.
+  ///
+  /// Subtrees are compared top-down and only descended into when their digests
+  /// differ while the values at their roots are equal, which is the case when the
+  /// remote digest is the one of the local value over the remote children, so only
+  /// the digests along the differing branches are read.
+  ///
+  /// ```
+  /// use structures::tree::{Side::*, Tree};
+  ///
+  /// let local = Tree::branch(1, Tree::leaf(2), Tree::leaf(3));
+  /// let remote = Tree::branch(1, Tree::leaf(2), Tree::leaf(4)).merkle_digests();
+  ///
+  /// assert_eq!(local.merkle_diff(&remote), [[Right]]);
+  /// ```
+  pub fn merkle_diff(&self, remote: &Tree<Digest>) -> Vec<Vec<Side>> {
+    let digests = self.merkle_digests();
+    let mut diffs = vec![];
+    let mut path = vec![];
+    let mut stack = vec![((self, &digests), remote, 0, None)];
+    while let Some(((a, da), b, depth, side)) = stack.pop() {
+      path.truncate(depth);
+      path.extend(side);
+      match ((a, da), b) {
+        ((Tree::Empty, _), Tree::Empty) => {}
+        ((Tree::Branch(..), Tree::Branch(x, ..)), Tree::Branch(y, ..)) if x == y => {}
+        ((Tree::Branch(va, la, ra), Tree::Branch(_, dla, dra)), Tree::Branch(y, lb, rb))
+          if branch_digest(hash(va), digest_of(lb), digest_of(rb)) == *y =>
+        {
+          stack.push(((&**ra, &**dra), &**rb, path.len(), Some(Side::Right)));
+          stack.push(((&**la, &**dla), &**lb, path.len(), Some(Side::Left)));
+        }
+        _ => diffs.push(path.clone()),
+      }
+    }
+    diffs
+  }
+}
+
+impl<T: Hash + PartialEq> Tree<T> {
+  /// Proof that the first occurrence of `value` in pre-order is in the tree.
+  pub fn merkle_proof(&self, value: &T) -> Option<MerkleProof> {
+    let digests = self.merkle_digests();
+    let mut path: Vec<(Side, Digest, Digest)> = vec![];
+    let mut stack = vec![(self, &digests, 0, None)];
+    while let Some((tree, digest, depth, step)) = stack.pop() {
+      if let (Tree::Branch(v, l, r), Tree::Branch(_, dl, dr)) = (tree, digest) {
+        path.truncate(depth);
+        path.extend(step);
+        if v == value {
+          let children = (digest_of(dl), digest_of(dr));
+          return Some(MerkleProof { children, steps: path.into_iter().rev().collect() });
+        }
+        let vh = hash(v);
+        stack.push((r, dr, path.len(), Some((Side::Right, vh, digest_of(dl)))));
+        stack.push((l, dl, path.len(), Some((Side::Left, vh, digest_of(dr)))));
+      }
+    }
+    None
+  }
+}
+
+fn digest_of(digests: &Tree<Digest>) -> Digest {
+  digests.value().cloned().unwrap_or_else(empty_digest)
+}
+
+fn hash<T: Hash>(value: &T) -> Digest {
+  let mut hasher = Sha256::new();
+  value.hash(&mut hasher);
+  hasher.digest()
+}
+
+fn empty_digest() -> Digest {
+  let mut hasher = Sha256::new();
+  hasher.update(&[0]);
+  hasher.digest()
+}
+
+fn branch_digest(value: Digest, left: Digest, right: Digest) -> Digest {
+  let mut hasher = Sha256::new();
+  hasher.update(&[1]);
+  for digest in &[value, left, right] {
+    hasher.update(digest);
+  }
+  hasher.digest()
+}
+
+/// SHA-256, as specified in FIPS 180-4
+#[derive(Clone)]
+struct Sha256 {
+  state: [u32; 8],
+  block: [u8; 64],
+  block_len: usize,
+  /// Number of bytes written so far.
+  len: u64,
+}
+
+const K: [u32; 64] = [
+  0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5, 0xd807aa98,
+  0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786,
+  0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8,
+  0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13,
+  0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819,
+  0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a,
+  0x5b9cca4f, 0x682e6ff3, 0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+  0xc67178f2,
+];
+
+impl Sha256 {
+  fn new() -> Self {
+    Self {
+      state: [0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19],
+      block: [0; 64],
+      block_len: 0,
+      len: 0,
+    }
+  }
+
+  fn update(&mut self, mut bytes: &[u8]) {
+    self.len = self.len.wrapping_add(bytes.len() as u64);
+    while !bytes.is_empty() {
+      let n = bytes.len().min(64 - self.block_len);
+      self.block[self.block_len..self.block_len + n].copy_from_slice(&bytes[..n]);
+      self.block_len += n;
+      bytes = &bytes[n..];
+      if self.block_len == 64 {
+        self.compress();
+        self.block_len = 0;
+      }
+    }
+  }
+
+  fn digest(mut self) -> Digest {
+    let bits = self.len.wrapping_mul(8);
+    self.update(&[0x80]);
+    while self.block_len != 56 {
+      self.update(&[0]);
+    }
+    self.update(&bits.to_be_bytes());
+    let mut digest = [0; 32];
+    for (bytes, word) in digest.chunks_exact_mut(4).zip(&self.state) {
+      bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+  }
+
+  fn compress(&mut self) {
+    let mut w = [0u32; 64];
+    for (i, bytes) in self.block.chunks_exact(4).enumerate() {
+      w[i] = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    for i in 16..64 {
+      let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+      let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+      w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+    }
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+    for i in 0..64 {
+      let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+      let ch = (e & f) ^ (!e & g);
+      let t1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+      let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+      let maj = (a & b) ^ (a & c) ^ (b & c);
+      let t2 = s0.wrapping_add(maj);
+      h = g;
+      g = f;
+      f = e;
+      e = d.wrapping_add(t1);
+      d = c;
+      c = b;
+      b = a;
+      a = t1.wrapping_add(t2);
+    }
+    for (x, y) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+      *x = x.wrapping_add(y);
+    }
+  }
+}
+
+/// Integers are written in little-endian so that digests do not depend on the platform.
+impl Hasher for Sha256 {
+  fn write(&mut self, bytes: &[u8]) {
+    self.update(bytes);
+  }
+
+  fn write_u16(&mut self, i: u16) {
+    self.update(&i.to_le_bytes());
+  }
+
+  fn write_u32(&mut self, i: u32) {
+    self.update(&i.to_le_bytes());
+  }
+
+  fn write_u64(&mut self, i: u64) {
+    self.update(&i.to_le_bytes());
+  }
+
+  fn write_u128(&mut self, i: u128) {
+    self.update(&i.to_le_bytes());
+  }
+
+  fn write_usize(&mut self, i: usize) {
+    self.write_u64(i as u64);
+  }
+
+  fn write_isize(&mut self, i: isize) {
+    self.write_i64(i as i64);
+  }
+
+  fn finish(&self) -> u64 {
+    let digest = self.clone().digest();
+    u64::from_le_bytes([digest[0], digest[1], digest[2], digest[3], digest[4], digest[5], digest[6], digest[7]])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Digest, Sha256, Side::*};
+  use crate::tree::{tests::new_tree, Tree};
+
+  #[test]
+  fn merkle_root() {
+    assert_eq!(new_tree().merkle_root(), new_tree().merkle_root());
+    assert_ne!(Tree::<i32>::empty().merkle_root(), Tree::leaf(0).merkle_root());
+    assert_ne!(
+      Tree::branch(1, Tree::leaf(2), Tree::empty()).merkle_root(),
+      Tree::branch(1, Tree::empty(), Tree::leaf(2)).merkle_root()
+    );
+    assert_ne!(new_tree().merkle_root(), parse("1(2(4,5),3(6,8))").merkle_root());
+  }
+
+  #[test]
+  fn merkle_digests() {
+    let digests = new_tree().merkle_digests();
+    assert_eq!(digests.value(), Some(&new_tree().merkle_root()));
+    assert_eq!(digests.left().unwrap().value(), Some(&parse("2(4,5)").merkle_root()));
+    assert_eq!(digests.size(), 7);
+  }
+
+  #[test]
+  fn merkle_proof() {
+    let tree = new_tree();
+    let root = tree.merkle_root();
+    for v in 1..=7 {
+      let proof = tree.merkle_proof(&v).unwrap();
+      assert!(proof.verify(root, &v));
+      assert!(!proof.verify(root, &(v + 10)));
+      let mut other = root;
+      other[0] ^= 1;
+      assert!(!proof.verify(other, &v));
+    }
+    assert_eq!(tree.merkle_proof(&6).unwrap().path(), [Right, Left]);
+    assert_eq!(tree.merkle_proof(&1).unwrap().path(), []);
+    assert_eq!(tree.merkle_proof(&8), None);
+    assert!(!tree.merkle_proof(&6).unwrap().verify(parse("1(2(4,5),3(6,8))").merkle_root(), &6));
+  }
+
+  #[test]
+  fn merkle_diff() {
+    let tree = new_tree();
+    let remote = |s| parse(s).merkle_digests();
+    assert_eq!(tree.merkle_diff(&new_tree().merkle_digests()), Vec::<Vec<_>>::new());
+    assert_eq!(tree.merkle_diff(&remote("1(2(4,5),3(6,8))")), [[Right, Right]]);
+    assert_eq!(tree.merkle_diff(&remote("1(2(0,5),3(-,7))")), [[Left, Left], [Right, Left]]);
+    assert_eq!(tree.merkle_diff(&remote("1(9(4,5),3(6,7))")), [[Left]]);
+    assert_eq!(tree.merkle_diff(&remote("0(2(4,5),3(6,7))")), [[]]);
+    assert_eq!(tree.merkle_diff(&remote("1(2(4,5),3(6,7(8,-)))")), [[Right, Right, Left]]);
+    assert_eq!(Tree::<i32>::empty().merkle_diff(&remote("1")), [[]]);
+  }
+
+  #[test]
+  fn merkle_diff_digests_only() {
+    // Digests sent by a replica holding "1(2(4,5),3(6,8))", with the unchanged
+    // subtrees collapsed to their digest.
+    let remote = {
+      let digests = parse("1(2(4,5),3(6,8))").merkle_digests();
+      let collapse = |t: &Tree<Digest>| Tree::leaf(*t.value().unwrap());
+      match &digests {
+        Tree::Branch(d, l, r) => match &**r {
+          Tree::Branch(dr, rl, rr) => Tree::branch(*d, collapse(l), Tree::branch(*dr, collapse(rl), collapse(rr))),
+          Tree::Empty => unreachable!(),
+        },
+        Tree::Empty => unreachable!(),
+      }
+    };
+    assert_eq!(new_tree().merkle_diff(&remote), [[Right, Right]]);
+  }
+
+  #[test]
+  fn sha256() {
+    let digest = |s: &str| {
+      let mut hasher = Sha256::new();
+      hasher.update(s.as_bytes());
+      hex(&hasher.digest())
+    };
+    assert_eq!(digest(""), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+    assert_eq!(digest("abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    assert_eq!(
+      digest("abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+      "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+    );
+    assert_eq!(digest(&"a".repeat(1000)), "41edece42d63e8d9bf515a9ba6932e1c20cbc9f5a5d134645adb5db1b9737ea3");
+  }
+
+  #[test]
+  fn pinned() {
+    assert_eq!(
+      hex(&Tree::<i32>::empty().merkle_root()),
+      "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d"
+    );
+    assert_eq!(hex(&new_tree().merkle_root()), "77725241276e12ef19f46a89c6d8bbfc0b5c674f525113f9b40d1821be537f2b");
+  }
+
+  #[test]
+  fn widened_integers() {
+    assert_eq!(Tree::leaf(-3isize).merkle_root(), Tree::leaf(-3i64).merkle_root());
+    assert_eq!(Tree::leaf(isize::MIN).merkle_root(), Tree::leaf(isize::MIN as i64).merkle_root());
+    assert_eq!(Tree::leaf(7usize).merkle_root(), Tree::leaf(7u64).merkle_root());
+  }
+
+  fn hex(digest: &[u8]) -> String {
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+  }
+
+  fn parse(s: &str) -> Tree<i32> {
+    Tree::parse(s).unwrap()
+  }
+}
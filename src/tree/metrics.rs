@@ -0,0 +1,217 @@
+use std::cmp::max;
+use std::collections::VecDeque;
+
+use super::{TraverseOrder, Tree};
+
+impl<T> Tree<T> {
+  /// Number of values in the tree.
+  pub fn size(&self) -> usize {
+    self.iter(TraverseOrder::PreOrder).count()
+  }
+
+  /// Number of levels in the tree, `0` for an empty tree.
+  pub fn height(&self) -> usize {
+    self.fold(|| 0, |_, l, r| 1 + max(l, r))
+  }
+
+  /// Number of branches without children.
+  pub fn leaf_count(&self) -> usize {
+    self.branches().filter(|(_, l, r)| l.is_empty() && r.is_empty()).count()
+  }
+
+  /// Whether the heights of the two subtrees of every branch differ by at most one.
+  pub fn is_balanced(&self) -> bool {
+    let balanced = |l: Option<usize>, r: Option<usize>| match (l, r) {
+      (Some(l), Some(r)) if max(l, r) - l.min(r) <= 1 => Some(1 + max(l, r)),
+      _ => None,
+    };
+    self.fold(|| Some(0), |_, l, r| balanced(l, r)).is_some()
+  }
+
+  /// Whether every level is filled, except possibly the last, which is filled from the left.
+  pub fn is_complete(&self) -> bool {
+    let mut queue = VecDeque::new();
+    queue.push_back(self);
+    let mut seen_empty = false;
+    while let Some(tree) = queue.pop_front() {
+      match tree {
+        Tree::Empty => seen_empty = true,
+        Tree::Branch(_, l, r) => {
+          if seen_empty {
+            return false;
+          }
+          queue.push_back(l);
+          queue.push_back(r);
+        }
+      }
+    }
+    true
+  }
+
+  /// Whether every branch has either no children or two children.
+  pub fn is_full(&self) -> bool {
+    self.branches().all(|(_, l, r)| l.is_empty() == r.is_empty())
+  }
+
+  /// Number of edges on the longest path between two values.
+  pub fn diameter(&self) -> usize {
+    let diameter = |(hl, dl): (usize, usize), (hr, dr): (usize, usize)| (1 + max(hl, hr), max(hl + hr, max(dl, dr)));
+    self.fold(|| (0, 0), |_, l, r| diameter(l, r)).1
+  }
+
+  fn branches(&self) -> impl Iterator<Item = (&T, &Self, &Self)> {
+    let mut stack = vec![self];
+    std::iter::from_fn(move || {
+      while let Some(tree) = stack.pop() {
+        if let Tree::Branch(v, l, r) = tree {
+          stack.push(r);
+          stack.push(l);
+          return Some((v, &**l, &**r));
+        }
+      }
+      None
+    })
+  }
+}
+
+impl<T: PartialEq> Tree<T> {
+  /// Values on the path from the root to the first occurrence of `value` in pre-order.
+  pub fn path_to(&self, value: &T) -> Option<Vec<&T>> {
+    let mut path = vec![];
+    let mut stack = vec![(self, 0)];
+    while let Some((tree, depth)) = stack.pop() {
+      if let Tree::Branch(v, l, r) = tree {
+        path.truncate(depth);
+        path.push(v);
+        if v == value {
+          return Some(path);
+        }
+        stack.push((r, depth + 1));
+        stack.push((l, depth + 1));
+      }
+    }
+    None
+  }
+
+  /// Deepest value that has both `a` and `b` as descendants, where a value descends from itself.
+  pub fn lowest_common_ancestor(&self, a: &T, b: &T) -> Option<&T> {
+    let path_a = self.path_to(a)?;
+    let path_b = self.path_to(b)?;
+    path_a.into_iter().zip(path_b).take_while(|(x, y)| std::ptr::eq(*x, *y)).last().map(|(x, _)| x)
+  }
+}
+
+impl<T: Ord> Tree<T> {
+  /// Whether the in-order sequence is strictly increasing.
+  pub fn is_bst(&self) -> bool {
+    let mut iter = self.iter(TraverseOrder::InOrder);
+    let mut prev = match iter.next() {
+      Some(v) => v,
+      None => return true,
+    };
+    for v in iter {
+      if prev >= v {
+        return false;
+      }
+      prev = v;
+    }
+    true
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::tree::{tests::new_tree, Tree};
+
+  #[test]
+  fn size() {
+    assert_eq!(Tree::<()>::empty().size(), 0);
+    assert_eq!(Tree::leaf(1).size(), 1);
+    assert_eq!(new_tree().size(), 7);
+  }
+
+  #[test]
+  fn height() {
+    assert_eq!(Tree::<()>::empty().height(), 0);
+    assert_eq!(Tree::leaf(1).height(), 1);
+    assert_eq!(new_tree().height(), 3);
+    assert_eq!(new_skewed_tree().height(), 3);
+  }
+
+  #[test]
+  fn leaf_count() {
+    assert_eq!(Tree::<()>::empty().leaf_count(), 0);
+    assert_eq!(Tree::leaf(1).leaf_count(), 1);
+    assert_eq!(new_tree().leaf_count(), 4);
+    assert_eq!(new_skewed_tree().leaf_count(), 1);
+  }
+
+  #[test]
+  fn is_balanced() {
+    assert!(Tree::<()>::empty().is_balanced());
+    assert!(new_tree().is_balanced());
+    assert!(Tree::branch(1, Tree::leaf(2), Tree::empty()).is_balanced());
+    assert!(!new_skewed_tree().is_balanced());
+  }
+
+  #[test]
+  fn is_complete() {
+    assert!(Tree::<()>::empty().is_complete());
+    assert!(new_tree().is_complete());
+    assert!(Tree::branch(1, Tree::leaf(2), Tree::empty()).is_complete());
+    assert!(!Tree::branch(1, Tree::empty(), Tree::leaf(3)).is_complete());
+    assert!(!new_skewed_tree().is_complete());
+  }
+
+  #[test]
+  fn is_full() {
+    assert!(Tree::<()>::empty().is_full());
+    assert!(new_tree().is_full());
+    assert!(!Tree::branch(1, Tree::leaf(2), Tree::empty()).is_full());
+  }
+
+  #[test]
+  fn is_bst() {
+    assert!(Tree::<i32>::empty().is_bst());
+    assert!(!new_tree().is_bst());
+    assert!(Tree::branch(4, Tree::branch(2, Tree::leaf(1), Tree::leaf(3)), Tree::leaf(5)).is_bst());
+    assert!(!Tree::branch(2, Tree::leaf(2), Tree::empty()).is_bst());
+  }
+
+  #[test]
+  fn diameter() {
+    assert_eq!(Tree::<()>::empty().diameter(), 0);
+    assert_eq!(Tree::leaf(1).diameter(), 0);
+    assert_eq!(new_tree().diameter(), 4);
+    assert_eq!(new_skewed_tree().diameter(), 2);
+  }
+
+  #[test]
+  fn path_to() {
+    assert_eq!(new_tree().path_to(&5), Some(vec![&1, &2, &5]));
+    assert_eq!(new_tree().path_to(&1), Some(vec![&1]));
+    assert_eq!(new_tree().path_to(&8), None);
+  }
+
+  #[test]
+  fn lowest_common_ancestor() {
+    let tree = new_tree();
+    assert_eq!(tree.lowest_common_ancestor(&4, &5), Some(&2));
+    assert_eq!(tree.lowest_common_ancestor(&4, &7), Some(&1));
+    assert_eq!(tree.lowest_common_ancestor(&2, &5), Some(&2));
+    assert_eq!(tree.lowest_common_ancestor(&6, &6), Some(&6));
+    assert_eq!(tree.lowest_common_ancestor(&6, &8), None);
+  }
+
+  #[test]
+  fn deep() {
+    let tree = (0..100_000).fold(Tree::empty(), |t, x| Tree::branch(x, t, Tree::empty()));
+    assert_eq!(tree.height(), 100_000);
+    assert_eq!(tree.diameter(), 99_999);
+    assert!(!tree.is_balanced());
+  }
+
+  fn new_skewed_tree() -> Tree<i32> {
+    Tree::branch(1, Tree::branch(2, Tree::leaf(3), Tree::empty()), Tree::empty())
+  }
+}
@@ -0,0 +1,256 @@
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+
+use super::Tree;
+
+impl<T> Tree<T> {
+  /// In-order traversal in O(1) extra space using Morris threading.
+  ///
+  /// The tree is temporarily threaded while traversing and restored before returning,
+  /// including when `f` returns `false` to stop early or panics.
+  ///
+  /// ```
+  /// use structures::tree::Tree;
+  ///
+  /// let mut tree = Tree::branch(1, Tree::leaf(2), Tree::leaf(3));
+  /// let mut values = vec![];
+  ///
+  /// tree.morris_inorder(|&x| {
+  ///   values.push(x);
+  ///   true
+  /// });
+  ///
+  /// assert_eq!(values, [2, 1, 3]);
+  /// ```
+  pub fn morris_inorder(&mut self, f: impl FnMut(&T) -> bool) {
+    self.morris(false, f)
+  }
+
+  /// Pre-order traversal in O(1) extra space using Morris threading.
+  ///
+  /// See [`Tree::morris_inorder`].
+  pub fn morris_preorder(&mut self, f: impl FnMut(&T) -> bool) {
+    self.morris(true, f)
+  }
+
+  fn morris(&mut self, preorder: bool, mut f: impl FnMut(&T) -> bool) {
+    let mut threads = Threads { cur: self, threaded: false, live: 0, preorder, marker: PhantomData };
+    while let Some(v) = threads.next() {
+      // Values are never moved or written while the tree is threaded.
+      if !f(unsafe { &*v }) {
+        break;
+      }
+    }
+  }
+}
+
+/// Morris traversal state, threading the empty right child of each in-order
+/// predecessor back to its successor.
+///
+/// A thread is written into the allocation of the `Empty` right child it replaces,
+/// after the tag: `Tree` is `#[repr(u8)]`, so these bytes are padding of `Empty`
+/// and the child stays a valid `Empty` in its own `Box` while threaded. The padding
+/// of children that were never threaded is never read, as an empty right child
+/// holds a thread exactly when threads are left in the tree: they belong to the
+/// ancestors whose left subtree is being traversed, the innermost of which is the
+/// successor of a node without right child.
+///
+/// Dropping the state runs the traversal to the end, which removes all remaining threads.
+struct Threads<'a, T> {
+  cur: *mut Tree<T>,
+  /// Whether `cur` has been reached through a thread, its left subtree being done.
+  threaded: bool,
+  /// Number of threads in the tree.
+  live: usize,
+  preorder: bool,
+  marker: PhantomData<&'a mut Tree<T>>,
+}
+
+impl<'a, T> Threads<'a, T> {
+  fn next(&mut self) -> Option<*const T> {
+    unsafe {
+      loop {
+        let (v, l) = match &*self.cur {
+          Tree::Empty => return None,
+          Tree::Branch(v, l, _) => (v as *const T, child(l)),
+        };
+        if (*l).is_empty() {
+          self.advance();
+          return Some(v);
+        }
+        // Right child of the in-order predecessor.
+        let mut slot = right(l);
+        while !(*slot).is_empty() {
+          slot = right(slot);
+        }
+        if self.threaded {
+          ptr::write(slot, Tree::Empty);
+          self.live -= 1;
+          self.advance();
+          if self.preorder {
+            continue;
+          }
+          return Some(v);
+        }
+        thread(slot).write(self.cur);
+        self.live += 1;
+        self.cur = l;
+        if self.preorder {
+          return Some(v);
+        }
+      }
+    }
+  }
+
+  /// Moves to the right child, or along the thread stored in it.
+  unsafe fn advance(&mut self) {
+    let r = right(self.cur);
+    self.threaded = (*r).is_empty() && self.live > 0;
+    self.cur = if self.threaded { thread(r).read() } else { r };
+  }
+}
+
+/// Right child of a branch.
+unsafe fn right<T>(tree: *mut Tree<T>) -> *mut Tree<T> {
+  match &*tree {
+    Tree::Branch(_, _, r) => child(r),
+    Tree::Empty => unreachable!(),
+  }
+}
+
+/// Pointer to the child owned by a box, carrying the permissions of the box itself.
+///
+/// Nodes are only ever borrowed shared while threaded, so that the values given
+/// out stay valid, and children are written through their box.
+unsafe fn child<T>(tree: *const Box<Tree<T>>) -> *mut Tree<T> {
+  // `Box<Tree<T>>` has the same representation as `*mut Tree<T>`.
+  ptr::read(tree as *const *mut Tree<T>)
+}
+
+/// Location of the thread in an empty child, in the padding after the tag.
+///
+/// The `Branch` variant holds two pointers after the tag, so a `Tree` is aligned
+/// for and large enough to hold a pointer at that offset.
+unsafe fn thread<T>(tree: *mut Tree<T>) -> *mut *mut Tree<T> {
+  tree.cast::<u8>().add(mem::align_of::<*mut Tree<T>>()).cast()
+}
+
+impl<'a, T> Drop for Threads<'a, T> {
+  fn drop(&mut self) {
+    while self.next().is_some() {}
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::tree::{tests::new_tree, TraverseOrder::*, Tree};
+
+  #[test]
+  fn morris_inorder() {
+    let mut tree = new_tree();
+    let mut rs = vec![];
+    tree.morris_inorder(|&x| {
+      rs.push(x);
+      true
+    });
+    assert_eq!(rs, [4, 2, 5, 1, 6, 3, 7]);
+    assert_eq!(tree, new_tree());
+  }
+
+  #[test]
+  fn morris_preorder() {
+    let mut tree = new_tree();
+    let mut rs = vec![];
+    tree.morris_preorder(|&x| {
+      rs.push(x);
+      true
+    });
+    assert_eq!(rs, [1, 2, 4, 5, 3, 6, 7]);
+    assert_eq!(tree, new_tree());
+  }
+
+  #[test]
+  fn early_termination() {
+    for n in 0..8 {
+      let mut tree = new_tree();
+      let mut rs = vec![];
+      tree.morris_inorder(|&x| {
+        rs.push(x);
+        rs.len() < n
+      });
+      assert_eq!(rs, new_tree().iter(InOrder).cloned().take(n.max(1)).collect::<Vec<_>>());
+      assert_eq!(tree, new_tree());
+      let mut rs = vec![];
+      tree.morris_preorder(|&x| {
+        rs.push(x);
+        rs.len() < n
+      });
+      assert_eq!(rs, new_tree().iter(PreOrder).cloned().take(n.max(1)).collect::<Vec<_>>());
+      assert_eq!(tree, new_tree());
+    }
+  }
+
+  #[test]
+  fn panic_restores() {
+    let mut tree = new_tree();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+      tree.morris_inorder(|&x| {
+        assert_ne!(x, 1);
+        true
+      })
+    }));
+    assert!(result.is_err());
+    assert_eq!(tree, new_tree());
+  }
+
+  #[test]
+  fn keeps_boxes() {
+    let mut tree = new_tree();
+    let before = subtrees(&tree);
+    tree.morris_inorder(|_| true);
+    tree.morris_preorder(|&x| x != 5);
+    assert_eq!(subtrees(&tree), before);
+    assert_eq!(tree, new_tree());
+  }
+
+  #[test]
+  fn empty() {
+    let mut tree = Tree::<i32>::empty();
+    tree.morris_inorder(|_| panic!());
+    tree.morris_preorder(|_| panic!());
+    assert_eq!(tree, Tree::empty());
+  }
+
+  #[test]
+  fn deep() {
+    let mut tree = (0..100_000).fold(Tree::empty(), |t, x| Tree::branch(x, t, Tree::leaf(-x)));
+    let mut rs = vec![];
+    tree.morris_inorder(|&x| {
+      rs.push(x);
+      true
+    });
+    assert_eq!(rs, tree.iter(InOrder).cloned().collect::<Vec<_>>());
+    let mut rs = vec![];
+    tree.morris_preorder(|&x| {
+      rs.push(x);
+      true
+    });
+    assert_eq!(rs, tree.iter(PreOrder).cloned().collect::<Vec<_>>());
+    assert_eq!(tree.size(), 200_000);
+  }
+
+  /// Addresses of all the subtrees in pre-order.
+  fn subtrees(tree: &Tree<i32>) -> Vec<*const Tree<i32>> {
+    let mut addresses = vec![];
+    let mut stack = vec![tree];
+    while let Some(tree) = stack.pop() {
+      addresses.push(tree as *const _);
+      if let Tree::Branch(_, l, r) = tree {
+        stack.push(r);
+        stack.push(l);
+      }
+    }
+    addresses
+  }
+}
@@ -0,0 +1,195 @@
+use std::fmt::{Display, Write};
+
+use super::Tree;
+
+/// Orientation of [`Tree::render_ascii`]
+#[derive(Clone, Copy)]
+pub enum Orientation {
+  /// Root on the first line, children indented below, left before right
+  Sideways,
+  /// Root on top, children spread out below
+  TopDown,
+}
+
+impl<T: Display> Tree<T> {
+  /// Draws the tree with box-drawing connectors.
+  ///
+  /// Empty children of a branch with a single child are drawn as `-` in sideways orientation.
+  ///
+  /// ```
+  /// use structures::tree::{Orientation, Tree};
+  ///
+  /// let tree = Tree::branch(1, Tree::branch(2, Tree::leaf(4), Tree::leaf(5)), Tree::leaf(3));
+  ///
+  /// assert_eq!(tree.render_ascii(Orientation::Sideways), "1\n├── 2\n│   ├── 4\n│   └── 5\n└── 3");
+  /// assert_eq!(tree.render_ascii(Orientation::TopDown), " ┌─1┐\n┌2┐ 3\n4 5");
+  /// ```
+  pub fn render_ascii(&self, orientation: Orientation) -> String {
+    match orientation {
+      Orientation::Sideways => self.render_sideways(),
+      Orientation::TopDown => self.render_top_down(),
+    }
+  }
+
+  /// Generates a Graphviz DOT digraph.
+  ///
+  /// Branches are named `n0`, `n1`, ... in pre-order. With `show_empty`, empty children of
+  /// branches are drawn as point-shaped null markers named `e0`, `e1`, ... in pre-order.
+  pub fn to_dot(&self, show_empty: bool) -> String {
+    let mut dot = String::from("digraph {\n");
+    let (mut n, mut e) = (0, 0);
+    let mut stack = vec![(self, None)];
+    while let Some((tree, parent)) = stack.pop() {
+      let id = match tree {
+        Tree::Empty if show_empty && parent.is_some() => {
+          e += 1;
+          writeln!(dot, "  e{} [shape=point];", e - 1).unwrap();
+          format!("e{}", e - 1)
+        }
+        Tree::Empty => continue,
+        Tree::Branch(v, l, r) => {
+          n += 1;
+          let label = v.to_string().replace('\\', "\\\\").replace('"', "\\\"");
+          writeln!(dot, "  n{} [label=\"{}\"];", n - 1, label).unwrap();
+          stack.push((r, Some(n - 1)));
+          stack.push((l, Some(n - 1)));
+          format!("n{}", n - 1)
+        }
+      };
+      if let Some(parent) = parent {
+        writeln!(dot, "  n{} -> {};", parent, id).unwrap();
+      }
+    }
+    dot.push('}');
+    dot
+  }
+
+  fn render_sideways(&self) -> String {
+    let mut lines = vec![];
+    let mut stack = vec![(self, String::new(), "", true)];
+    while let Some((tree, prefix, connector, last)) = stack.pop() {
+      match tree {
+        Tree::Empty => lines.push(format!("{}{}-", prefix, connector)),
+        Tree::Branch(v, l, r) => {
+          lines.push(format!("{}{}{}", prefix, connector, v));
+          if !(l.is_empty() && r.is_empty()) {
+            let prefix = match (connector, last) {
+              ("", _) => prefix,
+              (_, true) => prefix + "    ",
+              (_, false) => prefix + "│   ",
+            };
+            stack.push((r, prefix.clone(), "└── ", true));
+            stack.push((l, prefix, "├── ", false));
+          }
+        }
+      }
+    }
+    lines.join("\n")
+  }
+
+  fn render_top_down(&self) -> String {
+    // Each non-empty subtree is rendered into a block of lines of equal width,
+    // along with the column its root is drawn at.
+    struct Block {
+      lines: Vec<String>,
+      width: usize,
+      root: usize,
+    }
+    let block = self.fold(|| None, |v, l: Option<Block>, r: Option<Block>| {
+      // Values are at least one column wide, so that connectors can reach them.
+      let s = format!("{:1}", v.to_string());
+      let u = s.chars().count();
+      let mid = (u - 1) / 2;
+      let pad = |n| " ".repeat(n);
+      let line = |n| "─".repeat(n);
+      Some(match (l, r) {
+        (None, None) => Block { lines: vec![s], width: u, root: mid },
+        (Some(l), None) => {
+          let n = l.width;
+          let mut lines = vec![format!("{}┌{}{}", pad(l.root), line(n - l.root - 1), s)];
+          lines.extend(l.lines.into_iter().map(|x| x + &pad(u)));
+          Block { lines, width: n + u, root: n + mid }
+        }
+        (None, Some(r)) => {
+          let m = r.width;
+          let mut lines = vec![format!("{}{}┐{}", s, line(r.root), pad(m - r.root - 1))];
+          lines.extend(r.lines.into_iter().map(|x| pad(u) + &x));
+          Block { lines, width: m + u, root: mid }
+        }
+        (Some(l), Some(r)) => {
+          let (n, m) = (l.width, r.width);
+          let first = format!("{}┌{}{}{}┐{}", pad(l.root), line(n - l.root - 1), s, line(r.root), pad(m - r.root - 1));
+          let mut lines = vec![first];
+          let (mut ls, mut rs) = (l.lines.into_iter(), r.lines.into_iter());
+          loop {
+            match (ls.next(), rs.next()) {
+              (None, None) => break,
+              (x, y) => lines.push(format!("{:n$}{}{:m$}", x.unwrap_or_default(), pad(u), y.unwrap_or_default())),
+            }
+          }
+          Block { lines, width: n + u + m, root: n + mid }
+        }
+      })
+    });
+    let block = match block {
+      Some(block) => block,
+      None => return "-".to_string(),
+    };
+    block.lines.iter().map(|x| x.trim_end()).collect::<Vec<_>>().join("\n")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::tree::{tests::new_tree, Orientation::*, Tree};
+
+  #[test]
+  fn render_sideways() {
+    assert_eq!(Tree::<i32>::empty().render_ascii(Sideways), "-");
+    assert_eq!(Tree::leaf(1).render_ascii(Sideways), "1");
+    let expected = ["1", "├── 2", "│   ├── 4", "│   └── 5", "└── 3", "    ├── 6", "    └── 7"];
+    assert_eq!(new_tree().render_ascii(Sideways), expected.join("\n"));
+    let expected = ["1", "├── -", "└── 3", "    ├── 6", "    └── -"];
+    assert_eq!(
+      Tree::branch(1, Tree::empty(), Tree::branch(3, Tree::leaf(6), Tree::empty())).render_ascii(Sideways),
+      expected.join("\n")
+    );
+  }
+
+  #[test]
+  fn render_top_down() {
+    assert_eq!(Tree::<i32>::empty().render_ascii(TopDown), "-");
+    assert_eq!(Tree::leaf(1).render_ascii(TopDown), "1");
+    let expected = [" ┌─1─┐", "┌2┐ ┌3┐", "4 5 6 7"];
+    assert_eq!(new_tree().render_ascii(TopDown), expected.join("\n"));
+    let expected = ["┌──10", "2┐", " 30"];
+    let tree = Tree::branch(10, Tree::branch(2, Tree::empty(), Tree::leaf(30)), Tree::empty());
+    assert_eq!(tree.render_ascii(TopDown), expected.join("\n"));
+    let tree = Tree::branch("a", Tree::leaf(""), Tree::leaf("b"));
+    assert_eq!(tree.render_ascii(TopDown), ["┌a┐", "  b"].join("\n"));
+    let tree = Tree::branch("", Tree::empty(), Tree::leaf(""));
+    assert_eq!(tree.render_ascii(TopDown), " ┐\n");
+  }
+
+  #[test]
+  fn to_dot() {
+    let tree = Tree::branch("a\"b", Tree::leaf("c"), Tree::empty());
+    let expected = ["digraph {", "  n0 [label=\"a\\\"b\"];", "  n1 [label=\"c\"];", "  n0 -> n1;", "}"];
+    assert_eq!(tree.to_dot(false), expected.join("\n"));
+    let expected = [
+      "digraph {",
+      "  n0 [label=\"a\\\"b\"];",
+      "  n1 [label=\"c\"];",
+      "  n0 -> n1;",
+      "  e0 [shape=point];",
+      "  n1 -> e0;",
+      "  e1 [shape=point];",
+      "  n1 -> e1;",
+      "  e2 [shape=point];",
+      "  n0 -> e2;",
+      "}",
+    ];
+    assert_eq!(tree.to_dot(true), expected.join("\n"));
+    assert_eq!(Tree::<i32>::empty().to_dot(true), "digraph {\n}");
+  }
+}
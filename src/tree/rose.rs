@@ -0,0 +1,412 @@
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fmt::{self, Write};
+use std::mem::{self, ManuallyDrop};
+use std::ptr;
+
+use super::{write_indent, Indented, TraverseOrder, Tree};
+
+/// Rose Tree
+///
+/// A tree where every node holds a value and any number of children.
+///
+/// # Examples
+///
+/// ```
+/// use structures::tree::{RoseTree, TraverseOrder, Tree};
+///
+/// let tree = RoseTree::node(1, vec![RoseTree::leaf(2), RoseTree::leaf(3), RoseTree::leaf(4)]);
+///
+/// assert_eq!(tree.iter(TraverseOrder::PostOrder).collect::<Vec<_>>(), [&2, &3, &4, &1]);
+/// assert_eq!(tree.get(&[1]).map(RoseTree::value), Some(&3));
+///
+/// // Left-child/right-sibling encoding
+/// let binary = Tree::from(tree);
+///
+/// assert_eq!(binary.to_string(), "1(2(-,3(-,4)),-)");
+/// ```
+pub struct RoseTree<T> {
+  value: T,
+  children: Vec<Self>,
+}
+
+impl<T> RoseTree<T> {
+  pub fn leaf(value: T) -> Self {
+    Self::node(value, vec![])
+  }
+
+  pub fn node(value: T, children: Vec<Self>) -> Self {
+    Self { value, children }
+  }
+
+  pub fn value(&self) -> &T {
+    &self.value
+  }
+
+  pub fn value_mut(&mut self) -> &mut T {
+    &mut self.value
+  }
+
+  pub fn children(&self) -> &[Self] {
+    &self.children
+  }
+
+  pub fn children_mut(&mut self) -> &mut Vec<Self> {
+    &mut self.children
+  }
+
+  pub fn push(&mut self, child: Self) {
+    self.children.push(child)
+  }
+
+  /// Follows `path` of child indices from this node.
+  pub fn get(&self, path: &[usize]) -> Option<&Self> {
+    path.iter().try_fold(self, |node, &i| node.children.get(i))
+  }
+
+  pub fn get_mut(&mut self, path: &[usize]) -> Option<&mut Self> {
+    path.iter().try_fold(self, |node, &i| node.children.get_mut(i))
+  }
+
+  /// Iterates the values in the given order.
+  ///
+  /// `InOrder` visits the first child, then the value, then the remaining children.
+  pub fn iter(&self, order: TraverseOrder) -> impl Iterator<Item = &T> {
+    use {IterState::*, TraverseOrder::*};
+    enum IterState<'a, T> {
+      T(&'a RoseTree<T>),
+      V(&'a T),
+    }
+    let mut stack = vec![IterState::T(self)];
+    std::iter::from_fn(move || {
+      while let Some(state) = stack.pop() {
+        match state {
+          T(node) => {
+            if let PostOrder = order {
+              stack.push(V(&node.value))
+            }
+            let (first, rest) = node.children.split_first().map_or((None, &[][..]), |(x, xs)| (Some(x), xs));
+            stack.extend(rest.iter().rev().map(T));
+            if let InOrder = order {
+              stack.push(V(&node.value))
+            }
+            stack.extend(first.map(T));
+            if let PreOrder = order {
+              stack.push(V(&node.value))
+            }
+          }
+          V(v) => {
+            return Some(v);
+          }
+        }
+      }
+      None
+    })
+  }
+
+  /// Iterates the values breadth-first, from the first child to the last on each level.
+  pub fn iter_level_order(&self) -> impl Iterator<Item = &T> {
+    let mut queue = VecDeque::from(vec![self]);
+    std::iter::from_fn(move || {
+      let node = queue.pop_front()?;
+      queue.extend(&node.children);
+      Some(&node.value)
+    })
+  }
+
+  /// Folds the tree bottom-up without recursion, combining each value with the results of its children.
+  pub fn fold<R>(&self, mut f: impl FnMut(&T, Vec<R>) -> R) -> R {
+    enum Task<'a, T> {
+      Fold(&'a RoseTree<T>),
+      Combine(&'a T, usize),
+    }
+    let mut tasks = vec![Task::Fold(self)];
+    let mut results = vec![];
+    while let Some(task) = tasks.pop() {
+      match task {
+        Task::Fold(node) => {
+          tasks.push(Task::Combine(&node.value, node.children.len()));
+          tasks.extend(node.children.iter().rev().map(Task::Fold));
+        }
+        Task::Combine(v, n) => {
+          let rs = results.split_off(results.len() - n);
+          results.push(f(v, rs));
+        }
+      }
+    }
+    results.pop().unwrap()
+  }
+
+  pub fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> RoseTree<U> {
+    self.fold(|v, children| RoseTree::node(f(v), children))
+  }
+
+  fn into_fold<R>(self, mut f: impl FnMut(T, Vec<R>) -> R) -> R {
+    enum Task<T> {
+      Fold(RoseTree<T>),
+      Combine(T, usize),
+    }
+    let mut tasks = vec![Task::Fold(self)];
+    let mut results = vec![];
+    while let Some(task) = tasks.pop() {
+      match task {
+        Task::Fold(node) => {
+          let (value, children) = node.into_parts();
+          tasks.push(Task::Combine(value, children.len()));
+          tasks.extend(children.into_iter().rev().map(Task::Fold));
+        }
+        Task::Combine(v, n) => {
+          let rs = results.split_off(results.len() - n);
+          results.push(f(v, rs));
+        }
+      }
+    }
+    results.pop().unwrap()
+  }
+
+  fn into_parts(self) -> (T, Vec<Self>) {
+    let mut node = ManuallyDrop::new(self);
+    let children = mem::take(&mut node.children);
+    // `node` is never dropped, so the value is read out exactly once.
+    (unsafe { ptr::read(&node.value) }, children)
+  }
+}
+
+impl<T: PartialEq> RoseTree<T> {
+  /// Child indices leading to the first occurrence of `value` in pre-order.
+  pub fn path_to(&self, value: &T) -> Option<Vec<usize>> {
+    let mut path = vec![];
+    let mut stack = vec![(self, 0, None)];
+    while let Some((node, depth, index)) = stack.pop() {
+      path.truncate(depth);
+      path.extend(index);
+      if &node.value == value {
+        return Some(path);
+      }
+      stack.extend(node.children.iter().enumerate().rev().map(|(i, child)| (child, path.len(), Some(i))));
+    }
+    None
+  }
+}
+
+impl<T> Drop for RoseTree<T> {
+  fn drop(&mut self) {
+    let mut stack = mem::take(&mut self.children);
+    while let Some(mut node) = stack.pop() {
+      stack.append(&mut node.children);
+    }
+  }
+}
+
+impl<T: PartialEq> PartialEq for RoseTree<T> {
+  fn eq(&self, other: &Self) -> bool {
+    let mut stack = vec![(self, other)];
+    while let Some((x, y)) = stack.pop() {
+      if x.value != y.value || x.children.len() != y.children.len() {
+        return false;
+      }
+      stack.extend(x.children.iter().zip(&y.children));
+    }
+    true
+  }
+}
+
+impl<T: Eq> Eq for RoseTree<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for RoseTree<T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    enum Step<'a, T> {
+      Node(&'a RoseTree<T>, usize),
+      Text(&'static str),
+      Indent(usize),
+    }
+
+    let pretty = f.alternate();
+    let mut stack = vec![Step::Node(self, 0)];
+    while let Some(step) = stack.pop() {
+      match step {
+        Step::Node(node, depth) if pretty => {
+          f.write_str("RoseTree {\n")?;
+          write_indent(f, depth + 1)?;
+          f.write_str("value: ")?;
+          write!(Indented { f: &mut *f, depth: depth + 1, on_newline: false }, "{:#?}", node.value)?;
+          f.write_str(",\n")?;
+          write_indent(f, depth + 1)?;
+          if node.children.is_empty() {
+            f.write_str("children: [],\n")?;
+            write_indent(f, depth)?;
+            f.write_str("}")?;
+            continue;
+          }
+          f.write_str("children: [\n")?;
+          stack.extend([Step::Text("}"), Step::Indent(depth), Step::Text("],\n"), Step::Indent(depth + 1)]);
+          for child in node.children.iter().rev() {
+            stack.extend([Step::Text(",\n"), Step::Node(child, depth + 2), Step::Indent(depth + 2)]);
+          }
+        }
+        Step::Node(node, depth) => {
+          f.write_str("RoseTree { value: ")?;
+          node.value.fmt(f)?;
+          f.write_str(", children: [")?;
+          stack.push(Step::Text("] }"));
+          for (i, child) in node.children.iter().enumerate().rev() {
+            stack.push(Step::Node(child, depth));
+            if i > 0 {
+              stack.push(Step::Text(", "));
+            }
+          }
+        }
+        Step::Text(text) => f.write_str(text)?,
+        Step::Indent(depth) => write_indent(f, depth)?,
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Encodes the tree with the first child on the left and the next sibling on the right.
+impl<T> From<RoseTree<T>> for Tree<T> {
+  fn from(tree: RoseTree<T>) -> Self {
+    tree.into_fold(|v, children: Vec<Tree<T>>| {
+      let siblings = children.into_iter().rev().fold(Tree::Empty, |siblings, child| {
+        let (v, l, _) = child.into_parts().unwrap();
+        Tree::branch(v, l, siblings)
+      });
+      Tree::branch(v, siblings, Tree::Empty)
+    })
+  }
+}
+
+/// Decodes a left-child/right-sibling encoded tree, failing with the input
+/// if it is empty or its root has a sibling.
+impl<T> TryFrom<Tree<T>> for RoseTree<T> {
+  type Error = Tree<T>;
+
+  fn try_from(tree: Tree<T>) -> Result<Self, Self::Error> {
+    match tree.right() {
+      Some(r) if r.is_empty() => {}
+      _ => return Err(tree),
+    }
+    let mut forest = tree.into_fold(VecDeque::new, |v, children, mut siblings| {
+      siblings.push_front(RoseTree::node(v, Vec::from(children)));
+      siblings
+    });
+    Ok(forest.pop_front().unwrap())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::RoseTree;
+  use crate::tree::{TraverseOrder::*, Tree};
+  use std::convert::TryFrom;
+
+  #[test]
+  fn get() {
+    let tree = new_tree();
+    assert_eq!(tree.get(&[]).map(RoseTree::value), Some(&1));
+    assert_eq!(tree.get(&[0, 1]).map(RoseTree::value), Some(&6));
+    assert_eq!(tree.get(&[2, 0]).map(RoseTree::value), Some(&7));
+    assert_eq!(tree.get(&[1, 0]), None);
+    assert_eq!(tree.get(&[3]), None);
+  }
+
+  #[test]
+  fn get_mut() {
+    let mut tree = new_tree();
+    *tree.get_mut(&[0, 1]).unwrap().value_mut() = 60;
+    tree.get_mut(&[1]).unwrap().push(RoseTree::leaf(9));
+    assert_eq!(tree.get(&[0, 1]).map(RoseTree::value), Some(&60));
+    assert_eq!(tree.get(&[1, 0]).map(RoseTree::value), Some(&9));
+  }
+
+  #[test]
+  fn path_to() {
+    let tree = new_tree();
+    assert_eq!(tree.path_to(&1), Some(vec![]));
+    assert_eq!(tree.path_to(&6), Some(vec![0, 1]));
+    assert_eq!(tree.path_to(&3), Some(vec![1]));
+    assert_eq!(tree.path_to(&7), Some(vec![2, 0]));
+    assert_eq!(tree.path_to(&8), None);
+  }
+
+  #[test]
+  fn iter() {
+    let h = |o| new_tree().iter(o).cloned().collect::<Vec<_>>();
+    assert_eq!(h(PreOrder), [1, 2, 5, 6, 3, 4, 7]);
+    assert_eq!(h(PostOrder), [5, 6, 2, 3, 7, 4, 1]);
+    assert_eq!(h(InOrder), [5, 2, 6, 1, 3, 7, 4]);
+  }
+
+  #[test]
+  fn iter_level_order() {
+    assert_eq!(new_tree().iter_level_order().cloned().collect::<Vec<_>>(), [1, 2, 3, 4, 5, 6, 7]);
+    assert_eq!(RoseTree::leaf(1).iter_level_order().collect::<Vec<_>>(), [&1]);
+  }
+
+  #[test]
+  fn fold() {
+    assert_eq!(new_tree().fold(|v, rs: Vec<i32>| v + rs.iter().sum::<i32>()), 28);
+    assert_eq!(new_tree().fold(|_, rs: Vec<usize>| 1 + rs.into_iter().max().unwrap_or(0)), 3);
+  }
+
+  #[test]
+  fn map() {
+    let tree = new_tree().map(|v| v * 10);
+    assert_eq!(tree.iter(PreOrder).cloned().collect::<Vec<_>>(), [10, 20, 50, 60, 30, 40, 70]);
+    assert_eq!(tree.get(&[2, 0]).map(RoseTree::value), Some(&70));
+  }
+
+  #[test]
+  fn binary() {
+    let binary = Tree::from(new_tree());
+    assert_eq!(binary.iter(PreOrder).cloned().collect::<Vec<_>>(), [1, 2, 5, 6, 3, 4, 7]);
+    assert_eq!(binary.right(), Some(&Tree::empty()));
+    assert_eq!(RoseTree::try_from(binary), Ok(new_tree()));
+    assert_eq!(RoseTree::try_from(Tree::<i32>::empty()), Err(Tree::empty()));
+    assert_eq!(
+      RoseTree::try_from(Tree::branch(1, Tree::empty(), Tree::leaf(2))),
+      Err(Tree::branch(1, Tree::empty(), Tree::leaf(2)))
+    );
+  }
+
+  #[test]
+  fn debug() {
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    struct RoseTree {
+      value: (i32, &'static str),
+      children: Vec<Self>,
+    }
+
+    let leaf = |value| RoseTree { value, children: vec![] };
+    let derived = RoseTree { value: (1, "a\nb"), children: vec![leaf((2, "")), leaf((3, "c"))] };
+    let tree = super::RoseTree::node((1, "a\nb"), vec![super::RoseTree::leaf((2, "")), super::RoseTree::leaf((3, "c"))]);
+    assert_eq!(format!("{:?}", tree), format!("{:?}", derived));
+    assert_eq!(format!("{:#?}", tree), format!("{:#?}", derived));
+    assert_eq!(format!("{:5?}", super::RoseTree::leaf(1)), "RoseTree { value:     1, children: [] }");
+  }
+
+  #[test]
+  fn deep() {
+    let tree = (0..100_000).fold(RoseTree::leaf(-1), |t, x| RoseTree::node(x, vec![t]));
+    assert_eq!(tree.fold(|_, rs: Vec<usize>| 1 + rs.into_iter().max().unwrap_or(0)), 100_001);
+    assert!(tree == tree.map(|&v| v));
+    assert!(format!("{:?}", tree).starts_with("RoseTree { value: 99999, children: [RoseTree {"));
+    let wide = RoseTree::node(0, (1..100_000).map(RoseTree::leaf).collect());
+    let binary = Tree::from(wide);
+    assert_eq!(binary.height(), 100_000);
+    assert_eq!(RoseTree::try_from(binary).map(|t| t.children().len()), Ok(99_999));
+  }
+
+  fn new_tree() -> RoseTree<i32> {
+    RoseTree::node(
+      1,
+      vec![
+        RoseTree::node(2, vec![RoseTree::leaf(5), RoseTree::leaf(6)]),
+        RoseTree::leaf(3),
+        RoseTree::node(4, vec![RoseTree::leaf(7)]),
+      ],
+    )
+  }
+}
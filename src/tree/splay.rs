@@ -0,0 +1,265 @@
+use std::cmp::Ordering;
+use std::iter::FromIterator;
+use std::mem;
+
+use super::{TraverseOrder, Tree};
+
+/// Splay Tree
+///
+/// A self-adjusting binary search tree. Every access moves the accessed key to the
+/// root, so recently accessed keys are cheap to reach again. Operations take
+/// O(log n) amortized time.
+///
+/// # Examples
+///
+/// ```
+/// use structures::tree::{SplayTree, TraverseOrder};
+///
+/// let mut tree = (1..=5).collect::<SplayTree<_>>();
+///
+/// assert!(tree.contains(&2));
+/// assert_eq!(tree.iter(TraverseOrder::PreOrder).next(), Some(&2));
+///
+/// let (small, large) = tree.split(&3);
+///
+/// assert_eq!(small.iter(TraverseOrder::InOrder).collect::<Vec<_>>(), [&1, &2]);
+/// assert_eq!(large.iter(TraverseOrder::InOrder).collect::<Vec<_>>(), [&3, &4, &5]);
+/// ```
+#[derive(Debug)]
+pub struct SplayTree<T> {
+  tree: Tree<T>,
+}
+
+impl<T> SplayTree<T> {
+  pub fn new() -> Self {
+    Self { tree: Tree::Empty }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.tree.is_empty()
+  }
+
+  /// Number of keys, in O(n).
+  pub fn len(&self) -> usize {
+    self.tree.size()
+  }
+
+  pub fn iter(&self, order: TraverseOrder) -> impl Iterator<Item = &T> {
+    self.tree.iter(order)
+  }
+
+  /// Top-down splay, moving the node where the search guided by `cmp` ends to the root.
+  fn splay(&mut self, mut cmp: impl FnMut(&T) -> Ordering) {
+    let mut tree = mem::replace(&mut self.tree, Tree::Empty);
+    // Nodes smaller than the target with their left subtree, and larger ones with their right subtree.
+    let mut left = vec![];
+    let mut right = vec![];
+    let (v, l, r) = loop {
+      let (v, l, r) = match tree.into_parts() {
+        Some(parts) => parts,
+        None => return,
+      };
+      match cmp(&v) {
+        Ordering::Less => match l.value().map(&mut cmp) {
+          None => break (v, l, r),
+          Some(Ordering::Less) => {
+            let (lv, ll, lr) = l.into_parts().unwrap();
+            let r = Tree::branch(v, lr, r);
+            if ll.is_empty() {
+              break (lv, ll, r);
+            }
+            right.push((lv, r));
+            tree = ll;
+          }
+          Some(_) => {
+            right.push((v, r));
+            tree = l;
+          }
+        },
+        Ordering::Greater => match r.value().map(&mut cmp) {
+          None => break (v, l, r),
+          Some(Ordering::Greater) => {
+            let (rv, rl, rr) = r.into_parts().unwrap();
+            let l = Tree::branch(v, l, rl);
+            if rr.is_empty() {
+              break (rv, l, rr);
+            }
+            left.push((rv, l));
+            tree = rr;
+          }
+          Some(_) => {
+            left.push((v, l));
+            tree = r;
+          }
+        },
+        Ordering::Equal => break (v, l, r),
+      }
+    };
+    let l = left.into_iter().rev().fold(l, |acc, (x, xl)| Tree::branch(x, xl, acc));
+    let r = right.into_iter().rev().fold(r, |acc, (x, xr)| Tree::branch(x, acc, xr));
+    self.tree = Tree::branch(v, l, r);
+  }
+
+  /// Joins two trees whose keys are all smaller in `self`.
+  fn join(mut self, other: Self) -> Self {
+    self.splay(|_| Ordering::Greater);
+    let tree = match self.tree.into_parts() {
+      None => other.tree,
+      Some((v, l, _)) => Tree::branch(v, l, other.tree),
+    };
+    Self { tree }
+  }
+}
+
+impl<T: Ord> SplayTree<T> {
+  /// Whether the tree holds `key`, moving it to the root if it does.
+  pub fn contains(&mut self, key: &T) -> bool {
+    self.splay(|v| key.cmp(v));
+    self.tree.value() == Some(key)
+  }
+
+  /// Inserts `key` at the root, returning `false` if it was already present.
+  pub fn insert(&mut self, key: T) -> bool {
+    if self.contains(&key) {
+      return false;
+    }
+    let tree = mem::replace(&mut self.tree, Tree::Empty);
+    self.tree = match tree.into_parts() {
+      None => Tree::leaf(key),
+      Some((v, l, r)) if key < v => Tree::branch(key, l, Tree::branch(v, Tree::Empty, r)),
+      Some((v, l, r)) => Tree::branch(key, Tree::branch(v, l, Tree::Empty), r),
+    };
+    true
+  }
+
+  pub fn remove(&mut self, key: &T) -> bool {
+    if !self.contains(key) {
+      return false;
+    }
+    let (_, l, r) = mem::replace(&mut self.tree, Tree::Empty).into_parts().unwrap();
+    *self = Self { tree: l }.join(Self { tree: r });
+    true
+  }
+
+  /// Splits the tree into the keys smaller than `key` and the others.
+  pub fn split(mut self, key: &T) -> (Self, Self) {
+    self.splay(|v| key.cmp(v));
+    let (l, r) = match self.tree.into_parts() {
+      None => (Tree::Empty, Tree::Empty),
+      Some((v, l, r)) if v < *key => (Tree::branch(v, l, Tree::Empty), r),
+      Some((v, l, r)) => (l, Tree::branch(v, Tree::Empty, r)),
+    };
+    (Self { tree: l }, Self { tree: r })
+  }
+
+  /// Merges with a tree whose keys are all greater.
+  ///
+  /// # Panics
+  ///
+  /// Panics if a key of `other` is not greater than every key of `self`.
+  pub fn merge(mut self, mut other: Self) -> Self {
+    self.splay(|_| Ordering::Greater);
+    other.splay(|_| Ordering::Less);
+    if let (Some(max), Some(min)) = (self.tree.value(), other.tree.value()) {
+      assert!(max < min, "merged trees overlap");
+    }
+    self.join(other)
+  }
+}
+
+impl<T> Default for SplayTree<T> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<T: Ord> FromIterator<T> for SplayTree<T> {
+  fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+    let mut tree = Self::new();
+    for key in iter {
+      tree.insert(key);
+    }
+    tree
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::SplayTree;
+  use crate::tree::TraverseOrder::*;
+
+  #[test]
+  fn insert_contains() {
+    let mut tree = SplayTree::new();
+    assert!(tree.insert(3));
+    assert!(tree.insert(1));
+    assert!(tree.insert(2));
+    assert!(!tree.insert(1));
+    assert_eq!(tree.len(), 3);
+    assert!(tree.contains(&1));
+    assert!(!tree.contains(&4));
+    assert_eq!(keys(&tree), [1, 2, 3]);
+  }
+
+  #[test]
+  fn splay() {
+    let mut tree = (1..=7).collect::<SplayTree<_>>();
+    for k in [4, 1, 7, 3, 3].iter() {
+      assert!(tree.contains(k));
+      assert_eq!(tree.iter(PreOrder).next(), Some(k));
+      assert_eq!(keys(&tree), [1, 2, 3, 4, 5, 6, 7]);
+    }
+    tree.contains(&0);
+    assert_eq!(tree.iter(PreOrder).next(), Some(&1));
+    tree.contains(&10);
+    assert_eq!(tree.iter(PreOrder).next(), Some(&7));
+  }
+
+  #[test]
+  fn remove() {
+    let mut tree = (1..=7).collect::<SplayTree<_>>();
+    assert!(tree.remove(&4));
+    assert!(!tree.remove(&4));
+    assert!(tree.remove(&1));
+    assert!(tree.remove(&7));
+    assert_eq!(keys(&tree), [2, 3, 5, 6]);
+    for k in [2, 3, 5, 6].iter() {
+      assert!(tree.remove(k));
+    }
+    assert!(tree.is_empty());
+  }
+
+  #[test]
+  fn split_merge() {
+    for k in 0..=8 {
+      let (l, r) = (1..=7).collect::<SplayTree<_>>().split(&k);
+      assert_eq!(keys(&l), (1..k.max(1)).collect::<Vec<_>>());
+      assert_eq!(keys(&r), (k.max(1)..=7).collect::<Vec<_>>());
+      assert_eq!(keys(&l.merge(r)), [1, 2, 3, 4, 5, 6, 7]);
+    }
+    let (l, r) = SplayTree::<i32>::new().split(&1);
+    assert!(l.merge(r).is_empty());
+  }
+
+  #[test]
+  #[should_panic]
+  fn merge_overlap() {
+    let l = vec![1, 3].into_iter().collect::<SplayTree<_>>();
+    let r = vec![2, 4].into_iter().collect::<SplayTree<_>>();
+    l.merge(r);
+  }
+
+  #[test]
+  fn deep() {
+    let mut tree = (0..100_000).collect::<SplayTree<_>>();
+    assert_eq!(tree.tree.height(), 100_000);
+    assert!(tree.contains(&0));
+    assert!(tree.tree.height() < 60_000);
+    assert!(tree.remove(&50_000));
+    assert_eq!(tree.len(), 99_999);
+  }
+
+  fn keys(tree: &SplayTree<i32>) -> Vec<i32> {
+    tree.iter(InOrder).cloned().collect()
+  }
+}
@@ -0,0 +1,410 @@
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+use super::Tree;
+
+/// Error returned by [`Tree::parse`], with the byte offset it occurred at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTreeError {
+  pub position: usize,
+  pub kind: ParseTreeErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseTreeErrorKind {
+  /// A character that cannot appear at this position
+  UnexpectedChar(char),
+  /// The input ended before the tree was complete
+  UnexpectedEnd,
+  /// A value that could not be parsed
+  InvalidValue(String),
+  /// A level-order value without a parent to attach to
+  DanglingValue,
+}
+
+impl fmt::Display for ParseTreeError {
+  fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+    match &self.kind {
+      ParseTreeErrorKind::UnexpectedChar(c) => write!(fmt, "unexpected character `{}`", c)?,
+      ParseTreeErrorKind::UnexpectedEnd => write!(fmt, "unexpected end of input")?,
+      ParseTreeErrorKind::InvalidValue(s) => write!(fmt, "invalid value `{}`", s)?,
+      ParseTreeErrorKind::DanglingValue => write!(fmt, "value has no parent")?,
+    }
+    write!(fmt, " at position {}", self.position)
+  }
+}
+
+impl std::error::Error for ParseTreeError {}
+
+impl<T: FromStr> Tree<T> {
+  /// Parses a tree from its compact form, as produced by `Display`, or its level-order form.
+  ///
+  /// In the compact form a branch is written `v(l,r)`, a leaf `v` and an empty tree `-`.
+  /// The level-order form lists values breadth-first, with `null` for empty children
+  /// and trailing `null`s omitted.
+  ///
+  /// Values are delimited by `(`, `)`, `,`, `[`, `]` and whitespace. A `\` makes the next
+  /// character part of the value, so `Display` escapes delimiters and backslashes in values,
+  /// as well as values that would read as `-` or `null`. Any value whose `Display` output is
+  /// non-empty and parses back with `FromStr` round-trips.
+  ///
+  /// ```
+  /// use structures::tree::Tree;
+  ///
+  /// let tree = Tree::branch(1, Tree::branch(2, Tree::empty(), Tree::leaf(5)), Tree::leaf(3));
+  ///
+  /// assert_eq!(tree.to_string(), "1(2(-,5),3)");
+  /// assert_eq!(Tree::parse("1(2(-,5),3)"), Ok(tree));
+  /// assert_eq!(Tree::<i32>::parse("[1,2,3,null,5]"), "1(2(-,5),3)".parse());
+  /// ```
+  pub fn parse(s: &str) -> Result<Self, ParseTreeError> {
+    let mut parser = Parser { s, pos: 0 };
+    parser.skip_whitespace();
+    let tree = if parser.peek() == Some('[') { parser.level_order()? } else { parser.compact()? };
+    parser.skip_whitespace();
+    match parser.peek() {
+      Some(c) => Err(parser.error(ParseTreeErrorKind::UnexpectedChar(c))),
+      None => Ok(tree),
+    }
+  }
+}
+
+impl<T: FromStr> FromStr for Tree<T> {
+  type Err = ParseTreeError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Self::parse(s)
+  }
+}
+
+impl<T: fmt::Display> Tree<T> {
+  /// Formats the tree in level-order form, like `[1,2,3,null,5]`.
+  pub fn to_level_order(&self) -> String {
+    let mut items = vec![];
+    let mut queue = VecDeque::new();
+    queue.push_back(self);
+    while let Some(tree) = queue.pop_front() {
+      match tree {
+        Tree::Empty => items.push(None),
+        Tree::Branch(v, l, r) => {
+          items.push(Some(escape(v.to_string(), "null")));
+          queue.push_back(l);
+          queue.push_back(r);
+        }
+      }
+    }
+    while let Some(None) = items.last() {
+      items.pop();
+    }
+    let items = items.into_iter().map(|x| x.unwrap_or_else(|| "null".to_string())).collect::<Vec<_>>();
+    format!("[{}]", items.join(","))
+  }
+}
+
+/// Formats the tree in compact form, like `1(2(-,5),3)`.
+impl<T: fmt::Display> fmt::Display for Tree<T> {
+  fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+    enum Token<'a, T> {
+      T(&'a Tree<T>),
+      S(&'static str),
+    }
+    let mut stack = vec![Token::T(self)];
+    while let Some(token) = stack.pop() {
+      match token {
+        Token::T(Tree::Empty) => fmt.write_str("-")?,
+        Token::T(Tree::Branch(v, l, r)) => {
+          fmt.write_str(&escape(v.to_string(), "-"))?;
+          if !(l.is_empty() && r.is_empty()) {
+            stack.extend([Token::S(")"), Token::T(r), Token::S(","), Token::T(l), Token::S("(")]);
+          }
+        }
+        Token::S(s) => fmt.write_str(s)?,
+      }
+    }
+    Ok(())
+  }
+}
+
+struct Parser<'a> {
+  s: &'a str,
+  pos: usize,
+}
+
+impl<'a> Parser<'a> {
+  fn compact<T: FromStr>(&mut self) -> Result<Tree<T>, ParseTreeError> {
+    enum Frame<T> {
+      Open(T),
+      Left(T, Tree<T>),
+    }
+    let mut stack = vec![];
+    loop {
+      self.skip_whitespace();
+      let pos = self.pos;
+      let mut tree = match self.token()? {
+        ("-", _) => Tree::Empty,
+        (_, token) => {
+          let v = Self::value(&token, pos)?;
+          self.skip_whitespace();
+          if self.peek() == Some('(') {
+            self.pos += 1;
+            stack.push(Frame::Open(v));
+            continue;
+          }
+          Tree::leaf(v)
+        }
+      };
+      loop {
+        self.skip_whitespace();
+        match stack.pop() {
+          None => return Ok(tree),
+          Some(Frame::Open(v)) => {
+            self.expect(',')?;
+            stack.push(Frame::Left(v, tree));
+            break;
+          }
+          Some(Frame::Left(v, l)) => {
+            self.expect(')')?;
+            tree = Tree::branch(v, l, tree);
+          }
+        }
+      }
+    }
+  }
+
+  fn level_order<T: FromStr>(&mut self) -> Result<Tree<T>, ParseTreeError> {
+    self.expect('[')?;
+    let mut items = vec![];
+    self.skip_whitespace();
+    if self.peek() == Some(']') {
+      self.pos += 1;
+      return Ok(Tree::Empty);
+    }
+    loop {
+      self.skip_whitespace();
+      let pos = self.pos;
+      let v = match self.token()? {
+        ("null", _) => None,
+        (_, token) => Some(Self::value(&token, pos)?),
+      };
+      items.push((v, pos));
+      self.skip_whitespace();
+      match self.peek() {
+        Some(',') => self.pos += 1,
+        Some(']') => {
+          self.pos += 1;
+          break;
+        }
+        Some(c) => return Err(self.error(ParseTreeErrorKind::UnexpectedChar(c))),
+        None => return Err(self.error(ParseTreeErrorKind::UnexpectedEnd)),
+      }
+    }
+
+    let mut children = vec![(None, None); items.len()];
+    let mut queue = VecDeque::new();
+    let mut next = 1;
+    if items[0].0.is_some() {
+      queue.push_back(0);
+    }
+    while let Some(parent) = queue.pop_front() {
+      for side in 0..2 {
+        if next < items.len() {
+          if items[next].0.is_some() {
+            if side == 0 {
+              children[parent].0 = Some(next);
+            } else {
+              children[parent].1 = Some(next);
+            }
+            queue.push_back(next);
+          }
+          next += 1;
+        }
+      }
+    }
+    if next < items.len() {
+      return Err(ParseTreeError {
+        position: items[next].1,
+        kind: ParseTreeErrorKind::DanglingValue,
+      });
+    }
+
+    // Children always come after their parents, so building backwards sees them first.
+    let mut trees = (0..items.len()).map(|_| None).collect::<Vec<_>>();
+    for (i, (v, _)) in items.into_iter().enumerate().rev() {
+      if let Some(v) = v {
+        let (l, r) = children[i];
+        let mut take = |c: Option<usize>| c.and_then(|c| trees[c].take()).unwrap_or(Tree::Empty);
+        let (l, r) = (take(l), take(r));
+        trees[i] = Some(Tree::branch(v, l, r));
+      }
+    }
+    Ok(trees[0].take().unwrap_or(Tree::Empty))
+  }
+
+  /// Reads a token, returning it both as written and with its escapes resolved.
+  fn token(&mut self) -> Result<(&'a str, Cow<'a, str>), ParseTreeError> {
+    let rest = &self.s[self.pos..];
+    let mut chars = rest.char_indices();
+    let mut len = rest.len();
+    let mut escaped = false;
+    while let Some((i, c)) = chars.next() {
+      if c == '\\' {
+        escaped = true;
+        if chars.next().is_none() {
+          self.pos += len;
+          return Err(self.error(ParseTreeErrorKind::UnexpectedEnd));
+        }
+      } else if is_delimiter(c) {
+        len = i;
+        break;
+      }
+    }
+    if len == 0 {
+      return Err(match self.peek() {
+        Some(c) => self.error(ParseTreeErrorKind::UnexpectedChar(c)),
+        None => self.error(ParseTreeErrorKind::UnexpectedEnd),
+      });
+    }
+    self.pos += len;
+    let token = &rest[..len];
+    if !escaped {
+      return Ok((token, Cow::Borrowed(token)));
+    }
+    let mut value = String::with_capacity(len);
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+      value.extend(if c == '\\' { chars.next() } else { Some(c) });
+    }
+    Ok((token, Cow::Owned(value)))
+  }
+
+  fn value<T: FromStr>(token: &str, pos: usize) -> Result<T, ParseTreeError> {
+    token.parse().map_err(|_| ParseTreeError {
+      position: pos,
+      kind: ParseTreeErrorKind::InvalidValue(token.to_string()),
+    })
+  }
+
+  fn expect(&mut self, c: char) -> Result<(), ParseTreeError> {
+    match self.peek() {
+      Some(x) if x == c => {
+        self.pos += c.len_utf8();
+        Ok(())
+      }
+      Some(x) => Err(self.error(ParseTreeErrorKind::UnexpectedChar(x))),
+      None => Err(self.error(ParseTreeErrorKind::UnexpectedEnd)),
+    }
+  }
+
+  fn peek(&self) -> Option<char> {
+    self.s[self.pos..].chars().next()
+  }
+
+  fn skip_whitespace(&mut self) {
+    let rest = &self.s[self.pos..];
+    self.pos += rest.len() - rest.trim_start().len();
+  }
+
+  fn error(&self, kind: ParseTreeErrorKind) -> ParseTreeError {
+    ParseTreeError { position: self.pos, kind }
+  }
+}
+
+fn is_delimiter(c: char) -> bool {
+  "(),[]".contains(c) || c.is_whitespace()
+}
+
+/// Escapes delimiters and backslashes in a formatted value, and the value itself if it
+/// would read as `marker`.
+fn escape(value: String, marker: &str) -> String {
+  if value == marker {
+    return format!("\\{}", value);
+  }
+  if !value.contains(|c| c == '\\' || is_delimiter(c)) {
+    return value;
+  }
+  let mut escaped = String::with_capacity(value.len() + 1);
+  for c in value.chars() {
+    if c == '\\' || is_delimiter(c) {
+      escaped.push('\\');
+    }
+    escaped.push(c);
+  }
+  escaped
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{ParseTreeError, ParseTreeErrorKind::*};
+  use crate::tree::{tests::new_tree, Tree};
+
+  #[test]
+  fn parse_compact() {
+    assert_eq!(Tree::parse("-"), Ok(Tree::<i32>::empty()));
+    assert_eq!(Tree::parse("1"), Ok(Tree::leaf(1)));
+    assert_eq!(Tree::parse("1(2(4,5),3(6,7))"), Ok(new_tree()));
+    assert_eq!(Tree::parse(" 1 ( 2 ( 4 , 5 ) , 3(6,7) ) "), Ok(new_tree()));
+    assert_eq!(Tree::parse("-1(-,-2)"), Ok(Tree::branch(-1, Tree::empty(), Tree::leaf(-2))));
+    assert_eq!("1(2(4,5),3(6,7))".parse(), Ok(new_tree()));
+  }
+
+  #[test]
+  fn parse_level_order() {
+    assert_eq!(Tree::parse("[]"), Ok(Tree::<i32>::empty()));
+    assert_eq!(Tree::parse("[null]"), Ok(Tree::<i32>::empty()));
+    assert_eq!(Tree::parse("[1,2,3,4,5,6,7]"), Ok(new_tree()));
+    assert_eq!(Tree::<i32>::parse("[ 1, 2, 3, null, 5 ]"), Tree::parse("1(2(-,5),3)"));
+    assert_eq!(Tree::<i32>::parse("[1,null,2,3]"), Tree::parse("1(-,2(3,-))"));
+  }
+
+  #[test]
+  fn parse_errors() {
+    let error = |position, kind| Err(ParseTreeError { position, kind });
+    assert_eq!(Tree::<i32>::parse(""), error(0, UnexpectedEnd));
+    assert_eq!(Tree::<i32>::parse("1(2,3"), error(5, UnexpectedEnd));
+    assert_eq!(Tree::<i32>::parse("1(2)"), error(3, UnexpectedChar(')')));
+    assert_eq!(Tree::<i32>::parse("1(2,x)"), error(4, InvalidValue("x".to_string())));
+    assert_eq!(Tree::<i32>::parse("1 2"), error(2, UnexpectedChar('2')));
+    assert_eq!(Tree::<i32>::parse("[1,,2]"), error(3, UnexpectedChar(',')));
+    assert_eq!(Tree::<i32>::parse("[1,null,null,4]"), error(13, DanglingValue));
+    assert_eq!(Tree::<i32>::parse("[1,2"), error(4, UnexpectedEnd));
+    assert_eq!(Tree::<i32>::parse("1(2,x)").unwrap_err().to_string(), "invalid value `x` at position 4");
+  }
+
+  #[test]
+  fn fmt() {
+    assert_eq!(Tree::<i32>::empty().to_string(), "-");
+    assert_eq!(new_tree().to_string(), "1(2(4,5),3(6,7))");
+    assert_eq!(Tree::branch(1, Tree::empty(), Tree::leaf(2)).to_string(), "1(-,2)");
+  }
+
+  #[test]
+  fn to_level_order() {
+    assert_eq!(Tree::<i32>::empty().to_level_order(), "[]");
+    assert_eq!(new_tree().to_level_order(), "[1,2,3,4,5,6,7]");
+    assert_eq!(Tree::parse("1(2(-,5),3)").map(|t: Tree<i32>| t.to_level_order()), Ok("[1,2,3,null,5]".to_string()));
+  }
+
+  #[test]
+  fn escapes() {
+    let s = |v: &str| v.to_string();
+    let tree = Tree::branch(
+      s("a(b, c)"),
+      Tree::branch(s("-"), Tree::leaf(s("[x]")), Tree::empty()),
+      Tree::branch(s("null"), Tree::empty(), Tree::leaf(s("back\\slash"))),
+    );
+    assert_eq!(tree.to_string(), r"a\(b\,\ c\)(\-(\[x\],-),null(-,back\\slash))");
+    assert_eq!(tree.to_level_order(), r"[a\(b\,\ c\),-,\null,\[x\],null,null,back\\slash]");
+    assert_eq!(Tree::<String>::parse(&tree.to_level_order()), Tree::parse(&tree.to_string()));
+    assert_eq!(Tree::parse(&tree.to_string()), Ok(tree));
+    assert_eq!(Tree::<String>::parse(r"1(\-,-)"), Ok(Tree::branch(s("1"), Tree::leaf(s("-")), Tree::empty())));
+    assert_eq!(Tree::<String>::parse(r"1(2,3\"), Err(ParseTreeError { position: 6, kind: UnexpectedEnd }));
+  }
+
+  #[test]
+  fn deep() {
+    let tree = (0..100_000).fold(Tree::empty(), |t, x| Tree::branch(x, t, Tree::empty()));
+    assert_eq!(Tree::parse(&tree.to_string()), Ok(tree));
+  }
+}
@@ -0,0 +1,333 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::iter::FromIterator;
+use std::mem;
+
+use super::{TraverseOrder, Tree};
+
+/// Treap
+///
+/// A binary search tree that is also a max-heap on random priorities, which keeps
+/// it balanced in expectation. Treaps built with the same seed and the same
+/// operations always have the same shape.
+///
+/// # Examples
+///
+/// ```
+/// use structures::tree::{TraverseOrder, Treap};
+///
+/// let mut treap = Treap::with_seed(42);
+///
+/// for x in vec![5, 1, 4, 2, 3] {
+///   treap.insert(x);
+/// }
+///
+/// assert!(treap.contains(&4));
+///
+/// let (small, large) = treap.split(&3);
+///
+/// assert_eq!(small.iter(TraverseOrder::InOrder).collect::<Vec<_>>(), [&1, &2]);
+/// assert_eq!(large.iter(TraverseOrder::InOrder).collect::<Vec<_>>(), [&3, &4, &5]);
+/// ```
+#[derive(Debug)]
+pub struct Treap<T> {
+  tree: Inner<T>,
+  rng: Rng,
+}
+
+/// Keys with their priority
+type Inner<T> = Tree<(T, u64)>;
+
+impl<T> Treap<T> {
+  /// Creates a treap seeded from the process-wide random state.
+  pub fn new() -> Self {
+    Self::with_seed(RandomState::new().build_hasher().finish())
+  }
+
+  pub fn with_seed(seed: u64) -> Self {
+    Self { tree: Tree::Empty, rng: Rng(seed) }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.tree.is_empty()
+  }
+
+  /// Number of keys, in O(n).
+  pub fn len(&self) -> usize {
+    self.tree.size()
+  }
+
+  pub fn iter(&self, order: TraverseOrder) -> impl Iterator<Item = &T> {
+    self.tree.iter(order).map(|(v, _)| v)
+  }
+}
+
+impl<T: Ord> Treap<T> {
+  pub fn contains(&self, key: &T) -> bool {
+    let mut tree = &self.tree;
+    while let Tree::Branch((v, _), l, r) = tree {
+      if key == v {
+        return true;
+      }
+      tree = if key < v { l } else { r };
+    }
+    false
+  }
+
+  /// Inserts `key`, returning `false` if it was already present.
+  pub fn insert(&mut self, key: T) -> bool {
+    if self.contains(&key) {
+      return false;
+    }
+    let priority = self.rng.next();
+    let mut tree = &mut self.tree;
+    while matches!(tree.value(), Some((_, p)) if *p > priority) {
+      tree = match tree {
+        Tree::Branch((v, _), l, r) => {
+          if key < *v {
+            l
+          } else {
+            r
+          }
+        }
+        Tree::Empty => unreachable!(),
+      };
+    }
+    let (l, r) = split(mem::replace(tree, Tree::Empty), &key);
+    *tree = Tree::branch((key, priority), l, r);
+    true
+  }
+
+  pub fn remove(&mut self, key: &T) -> bool {
+    let mut tree = &mut self.tree;
+    loop {
+      let ord = match tree.value() {
+        None => return false,
+        Some((v, _)) => key.cmp(v),
+      };
+      if ord.is_eq() {
+        break;
+      }
+      tree = match tree {
+        Tree::Branch(_, l, r) => {
+          if ord.is_lt() {
+            l
+          } else {
+            r
+          }
+        }
+        Tree::Empty => unreachable!(),
+      };
+    }
+    let (_, l, r) = mem::replace(tree, Tree::Empty).into_parts().unwrap();
+    *tree = merge(l, r);
+    true
+  }
+
+  /// Splits the treap into the keys smaller than `key` and the others.
+  pub fn split(mut self, key: &T) -> (Self, Self) {
+    let rng = Rng(self.rng.next());
+    let (l, r) = split(self.tree, key);
+    (Self { tree: l, rng: self.rng }, Self { tree: r, rng })
+  }
+
+  /// Merges with a treap whose keys are all greater.
+  ///
+  /// # Panics
+  ///
+  /// Panics if a key of `other` is not greater than every key of `self`.
+  pub fn merge(self, other: Self) -> Self {
+    let max = std::iter::successors(Some(&self.tree), |t| t.right()).filter_map(Tree::value).last();
+    let min = std::iter::successors(Some(&other.tree), |t| t.left()).filter_map(Tree::value).last();
+    if let (Some((max, _)), Some((min, _))) = (max, min) {
+      assert!(max < min, "merged treaps overlap");
+    }
+    Self { tree: merge(self.tree, other.tree), rng: self.rng }
+  }
+}
+
+impl<T> Default for Treap<T> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<T: Ord> FromIterator<T> for Treap<T> {
+  fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+    let mut treap = Self::new();
+    for key in iter {
+      treap.insert(key);
+    }
+    treap
+  }
+}
+
+/// Splits into the nodes smaller than `key` and the others, without recursion.
+fn split<T: Ord>(mut tree: Inner<T>, key: &T) -> (Inner<T>, Inner<T>) {
+  // Smaller nodes with their left subtree, and the others with their right subtree.
+  let mut left = vec![];
+  let mut right = vec![];
+  while let Some((v, l, r)) = tree.into_parts() {
+    if v.0 < *key {
+      left.push((v, l));
+      tree = r;
+    } else {
+      right.push((v, r));
+      tree = l;
+    }
+  }
+  let l = left.into_iter().rev().fold(Tree::Empty, |acc, (x, xl)| Tree::branch(x, xl, acc));
+  let r = right.into_iter().rev().fold(Tree::Empty, |acc, (x, xr)| Tree::branch(x, acc, xr));
+  (l, r)
+}
+
+/// Merges trees whose keys are all smaller in `a`, without recursion.
+fn merge<T>(mut a: Inner<T>, mut b: Inner<T>) -> Inner<T> {
+  // Nodes along the merged spine, with the subtree they keep and whether they come
+  // from `a`, keeping their left subtree, or from `b`, keeping their right one.
+  let mut spine = vec![];
+  let rest = loop {
+    let first = match (a.value(), b.value()) {
+      (None, _) => break b,
+      (_, None) => break a,
+      (Some((_, p)), Some((_, q))) => p > q,
+    };
+    if first {
+      let (v, l, r) = a.into_parts().unwrap();
+      spine.push((v, l, true));
+      a = r;
+    } else {
+      let (v, l, r) = b.into_parts().unwrap();
+      spine.push((v, r, false));
+      b = l;
+    }
+  };
+  spine.into_iter().rev().fold(rest, |acc, (v, kept, from_a)| {
+    if from_a {
+      Tree::branch(v, kept, acc)
+    } else {
+      Tree::branch(v, acc, kept)
+    }
+  })
+}
+
+/// SplitMix64 generator
+#[derive(Debug)]
+struct Rng(u64);
+
+impl Rng {
+  fn next(&mut self) -> u64 {
+    self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = self.0;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Inner, Treap};
+  use crate::tree::{TraverseOrder::*, Tree};
+
+  #[test]
+  fn insert_contains() {
+    let mut treap = Treap::with_seed(1);
+    assert!(treap.insert(3));
+    assert!(treap.insert(1));
+    assert!(treap.insert(2));
+    assert!(!treap.insert(1));
+    assert_eq!(treap.len(), 3);
+    assert!(treap.contains(&1));
+    assert!(!treap.contains(&4));
+    assert_eq!(keys(&treap), [1, 2, 3]);
+    check(&treap.tree);
+  }
+
+  #[test]
+  fn remove() {
+    let mut treap = new_treap(1..=7);
+    assert!(treap.remove(&4));
+    assert!(!treap.remove(&4));
+    assert!(treap.remove(&1));
+    assert!(treap.remove(&7));
+    assert_eq!(keys(&treap), [2, 3, 5, 6]);
+    check(&treap.tree);
+    for k in [2, 3, 5, 6].iter() {
+      assert!(treap.remove(k));
+    }
+    assert!(treap.is_empty());
+  }
+
+  #[test]
+  fn deterministic() {
+    let a = new_treap(vec![4, 8, 1, 9, 3, 7]);
+    let b = new_treap(vec![4, 8, 1, 9, 3, 7]);
+    assert_eq!(a.iter(PreOrder).collect::<Vec<_>>(), b.iter(PreOrder).collect::<Vec<_>>());
+    assert_eq!(a.iter(PostOrder).collect::<Vec<_>>(), b.iter(PostOrder).collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn split_merge() {
+    for k in 0..=8 {
+      let (l, r) = new_treap(1..=7).split(&k);
+      check(&l.tree);
+      check(&r.tree);
+      assert_eq!(keys(&l), (1..k.max(1)).collect::<Vec<_>>());
+      assert_eq!(keys(&r), (k.max(1)..=7).collect::<Vec<_>>());
+      let merged = l.merge(r);
+      check(&merged.tree);
+      assert_eq!(keys(&merged), [1, 2, 3, 4, 5, 6, 7]);
+    }
+  }
+
+  #[test]
+  #[should_panic]
+  fn merge_overlap() {
+    new_treap(vec![1, 3]).merge(new_treap(vec![2, 4]));
+  }
+
+  #[test]
+  fn balanced() {
+    let mut treap = new_treap(0..100_000);
+    assert!(treap.tree.height() < 100);
+    for k in (0..100_000).step_by(2) {
+      treap.remove(&k);
+    }
+    check(&treap.tree);
+    assert_eq!(treap.len(), 50_000);
+    assert!(treap.tree.height() < 100);
+  }
+
+  /// Checks key order and heap order on priorities.
+  fn check(tree: &Inner<i32>) {
+    let keys = tree.iter(InOrder).map(|(k, _)| k).collect::<Vec<_>>();
+    assert!(keys.windows(2).all(|w| w[0] < w[1]));
+    let mut stack = vec![tree];
+    let mut nodes = 0;
+    while let Some(node) = stack.pop() {
+      if let Tree::Branch((_, p), l, r) = node {
+        for child in [l, r].iter() {
+          if let Some((_, q)) = child.value() {
+            assert!(q <= p);
+          }
+          stack.push(child);
+        }
+        nodes += 1;
+      }
+    }
+    assert_eq!(nodes, keys.len());
+  }
+
+  fn keys(treap: &Treap<i32>) -> Vec<i32> {
+    treap.iter(InOrder).cloned().collect()
+  }
+
+  fn new_treap(keys: impl IntoIterator<Item = i32>) -> Treap<i32> {
+    let mut treap = Treap::with_seed(7);
+    for k in keys {
+      treap.insert(k);
+    }
+    treap
+  }
+}
@@ -0,0 +1,187 @@
+use std::mem;
+
+use super::Tree;
+
+/// Tree Zipper
+///
+/// A cursor focused on a subtree, remembering the path back to the root so that
+/// edits only rebuild the branches along that path.
+///
+/// # Examples
+///
+/// ```
+/// use structures::tree::{Tree, TreeZipper};
+///
+/// let tree = Tree::branch(1, Tree::branch(2, Tree::leaf(4), Tree::empty()), Tree::leaf(3));
+///
+/// let mut zipper = TreeZipper::new(tree);
+/// zipper.go_left();
+/// zipper.go_right();
+/// zipper.replace(Tree::leaf(5));
+///
+/// assert_eq!(zipper.root(), Tree::branch(1, Tree::branch(2, Tree::leaf(4), Tree::leaf(5)), Tree::leaf(3)));
+/// ```
+pub struct TreeZipper<T> {
+  focus: Tree<T>,
+  path: Vec<Crumb<T>>,
+}
+
+enum Crumb<T> {
+  /// Went left from a branch, keeping its value and right subtree
+  Left(T, Tree<T>),
+  /// Went right from a branch, keeping its value and left subtree
+  Right(T, Tree<T>),
+}
+
+impl<T> TreeZipper<T> {
+  pub fn new(tree: Tree<T>) -> Self {
+    Self { focus: tree, path: vec![] }
+  }
+
+  pub fn focus(&self) -> &Tree<T> {
+    &self.focus
+  }
+
+  pub fn is_root(&self) -> bool {
+    self.path.is_empty()
+  }
+
+  /// Moves to the left subtree, returns `false` if the focus is empty.
+  pub fn go_left(&mut self) -> bool {
+    match mem::replace(&mut self.focus, Tree::Empty).into_parts() {
+      Some((v, l, r)) => {
+        self.path.push(Crumb::Left(v, r));
+        self.focus = l;
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Moves to the right subtree, returns `false` if the focus is empty.
+  pub fn go_right(&mut self) -> bool {
+    match mem::replace(&mut self.focus, Tree::Empty).into_parts() {
+      Some((v, l, r)) => {
+        self.path.push(Crumb::Right(v, l));
+        self.focus = r;
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Moves to the parent, returns `false` at the root.
+  pub fn go_up(&mut self) -> bool {
+    match self.path.pop() {
+      Some(crumb) => {
+        let focus = mem::replace(&mut self.focus, Tree::Empty);
+        self.focus = match crumb {
+          Crumb::Left(v, r) => Tree::branch(v, focus, r),
+          Crumb::Right(v, l) => Tree::branch(v, l, focus),
+        };
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Replaces the focused subtree, returning the previous one.
+  pub fn replace(&mut self, tree: Tree<T>) -> Tree<T> {
+    mem::replace(&mut self.focus, tree)
+  }
+
+  /// Inserts `v` as the left child of the focus, with the previous left subtree as its left child.
+  ///
+  /// Returns `false` if the focus is empty.
+  pub fn insert_left(&mut self, v: T) -> bool {
+    match &mut self.focus {
+      Tree::Empty => false,
+      Tree::Branch(_, l, _) => {
+        let left = mem::replace(&mut **l, Tree::Empty);
+        **l = Tree::branch(v, left, Tree::Empty);
+        true
+      }
+    }
+  }
+
+  /// Removes the focused subtree, leaving an empty tree in its place.
+  pub fn delete(&mut self) -> Tree<T> {
+    self.replace(Tree::Empty)
+  }
+
+  /// Reassembles the whole tree.
+  pub fn root(mut self) -> Tree<T> {
+    while self.go_up() {}
+    self.focus
+  }
+}
+
+impl<T> From<Tree<T>> for TreeZipper<T> {
+  fn from(tree: Tree<T>) -> Self {
+    Self::new(tree)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::TreeZipper;
+  use crate::tree::{tests::new_tree, Tree};
+
+  #[test]
+  fn navigate() {
+    let mut zipper = TreeZipper::new(new_tree());
+    assert!(zipper.is_root());
+    assert!(!zipper.go_up());
+    assert!(zipper.go_right());
+    assert_eq!(zipper.focus().value(), Some(&3));
+    assert!(zipper.go_left());
+    assert_eq!(zipper.focus().value(), Some(&6));
+    assert!(zipper.go_left());
+    assert_eq!(zipper.focus(), &Tree::empty());
+    assert!(!zipper.go_left());
+    assert!(!zipper.go_right());
+    assert!(zipper.go_up());
+    assert!(zipper.go_up());
+    assert_eq!(zipper.focus().value(), Some(&3));
+    assert_eq!(zipper.root(), new_tree());
+  }
+
+  #[test]
+  fn replace() {
+    let mut zipper = TreeZipper::new(new_tree());
+    zipper.go_left();
+    assert_eq!(zipper.replace(Tree::leaf(8)), Tree::branch(2, Tree::leaf(4), Tree::leaf(5)));
+    assert_eq!(zipper.root(), Tree::branch(1, Tree::leaf(8), Tree::branch(3, Tree::leaf(6), Tree::leaf(7))));
+  }
+
+  #[test]
+  fn insert_left() {
+    let mut zipper = TreeZipper::new(new_tree());
+    zipper.go_right();
+    assert!(zipper.insert_left(8));
+    let right = Tree::branch(3, Tree::branch(8, Tree::leaf(6), Tree::empty()), Tree::leaf(7));
+    assert_eq!(zipper.root(), Tree::branch(1, Tree::branch(2, Tree::leaf(4), Tree::leaf(5)), right));
+    assert!(!TreeZipper::new(Tree::empty()).insert_left(1));
+  }
+
+  #[test]
+  fn delete() {
+    let mut zipper = TreeZipper::new(new_tree());
+    zipper.go_left();
+    zipper.go_right();
+    assert_eq!(zipper.delete(), Tree::leaf(5));
+    assert_eq!(zipper.focus(), &Tree::empty());
+    let left = Tree::branch(2, Tree::leaf(4), Tree::empty());
+    assert_eq!(zipper.root(), Tree::branch(1, left, Tree::branch(3, Tree::leaf(6), Tree::leaf(7))));
+  }
+
+  #[test]
+  fn deep() {
+    let mut zipper = TreeZipper::from((0..100_000).fold(Tree::empty(), |t, x| Tree::branch(x, t, Tree::empty())));
+    while zipper.go_left() {}
+    zipper.replace(Tree::leaf(-1));
+    let tree = zipper.root();
+    assert_eq!(tree.size(), 100_001);
+    assert_eq!(tree.value(), Some(&99_999));
+  }
+}
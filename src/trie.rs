@@ -0,0 +1,390 @@
+use std::fmt;
+use std::mem;
+
+/// Trie
+///
+/// A prefix tree over keys made of bytes, chars or any other ordered elements.
+/// In compressed mode, chains of nodes without values and with a single child are
+/// kept merged into one edge, making the trie a radix tree.
+///
+/// # Examples
+///
+/// ```
+/// use structures::trie::Trie;
+///
+/// let mut trie = Trie::compressed();
+///
+/// trie.insert(b"/api", 1);
+/// trie.insert(b"/api/users", 2);
+/// trie.insert(b"/app", 3);
+///
+/// assert_eq!(trie.get(b"/api"), Some(&1));
+/// assert_eq!(trie.longest_prefix(b"/api/users/42"), Some((&b"/api/users"[..], &2)));
+/// assert_eq!(trie.starts_with(b"/ap").map(|(_, &v)| v).collect::<Vec<_>>(), [1, 2, 3]);
+/// ```
+pub struct Trie<E, V> {
+  compressed: bool,
+  len: usize,
+  root: Node<E, V>,
+}
+
+/// Children are sorted by the first element of their edge label, and labels are never empty.
+struct Node<E, V> {
+  value: Option<V>,
+  children: Vec<(Vec<E>, Self)>,
+}
+
+impl<E, V> Trie<E, V> {
+  /// Creates a trie with one element per edge.
+  pub fn new() -> Self {
+    Self { compressed: false, len: 0, root: Node::new() }
+  }
+
+  /// Creates a radix tree.
+  pub fn compressed() -> Self {
+    Self { compressed: true, ..Self::new() }
+  }
+
+  pub fn is_compressed(&self) -> bool {
+    self.compressed
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  /// Number of nodes, including the root.
+  pub fn node_count(&self) -> usize {
+    let mut count = 0;
+    let mut stack = vec![&self.root];
+    while let Some(node) = stack.pop() {
+      count += 1;
+      stack.extend(node.children.iter().map(|(_, child)| child));
+    }
+    count
+  }
+}
+
+impl<E: Ord + Clone, V> Trie<E, V> {
+  /// Inserts a value, returning the previous value of `key`.
+  pub fn insert(&mut self, key: &[E], value: V) -> Option<V> {
+    let mut node = &mut self.root;
+    let mut rest = key;
+    while let Some(first) = rest.first() {
+      let i = match node.find(first) {
+        Ok(i) => i,
+        Err(i) => {
+          let child = if self.compressed {
+            (rest.to_vec(), Node::leaf(value))
+          } else {
+            let tail = rest[1..].iter().rev().fold(Node::leaf(value), |child, e| Node::edge(vec![e.clone()], child));
+            (vec![first.clone()], tail)
+          };
+          node.children.insert(i, child);
+          self.len += 1;
+          return None;
+        }
+      };
+      let (label, child) = &mut node.children[i];
+      let common = label.iter().zip(rest).take_while(|(a, b)| a == b).count();
+      if common < label.len() {
+        let suffix = label.split_off(common);
+        let grandchild = mem::replace(child, Node::new());
+        child.children.push((suffix, grandchild));
+      }
+      node = &mut node.children[i].1;
+      rest = &rest[common..];
+    }
+    let old = node.value.replace(value);
+    if old.is_none() {
+      self.len += 1;
+    }
+    old
+  }
+
+  pub fn get(&self, key: &[E]) -> Option<&V> {
+    let mut node = &self.root;
+    let mut rest = key;
+    while let Some(first) = rest.first() {
+      let (label, child) = &node.children[node.find(first).ok()?];
+      rest = rest.strip_prefix(&label[..])?;
+      node = child;
+    }
+    node.value.as_ref()
+  }
+
+  pub fn get_mut(&mut self, key: &[E]) -> Option<&mut V> {
+    let mut node = &mut self.root;
+    let mut rest = key;
+    while let Some(first) = rest.first() {
+      let i = node.find(first).ok()?;
+      let (label, child) = &mut node.children[i];
+      rest = rest.strip_prefix(&label[..])?;
+      node = child;
+    }
+    node.value.as_mut()
+  }
+
+  pub fn contains_key(&self, key: &[E]) -> bool {
+    self.get(key).is_some()
+  }
+
+  /// Removes the value of `key`, pruning the nodes left without values.
+  pub fn remove(&mut self, key: &[E]) -> Option<V> {
+    self.get(key)?;
+    // Children along the path are detached while walking down, with their index and label.
+    let mut path: Vec<(usize, Vec<E>, Node<E, V>)> = vec![];
+    let mut rest = key;
+    while let Some(first) = rest.first() {
+      let node = path.last_mut().map_or(&mut self.root, |(_, _, node)| node);
+      let i = node.find(first).unwrap();
+      let (label, child) = mem::replace(&mut node.children[i], (vec![], Node::new()));
+      rest = &rest[label.len()..];
+      path.push((i, label, child));
+    }
+    let value = path.last_mut().map_or(&mut self.root, |(_, _, node)| node).value.take();
+    while let Some((i, mut label, mut child)) = path.pop() {
+      let parent = path.last_mut().map_or(&mut self.root, |(_, _, node)| node);
+      if child.value.is_none() {
+        match child.children.len() {
+          0 => {
+            parent.children.remove(i);
+            continue;
+          }
+          1 if self.compressed => {
+            let (suffix, grandchild) = child.children.pop().unwrap();
+            label.extend(suffix);
+            child = grandchild;
+          }
+          _ => {}
+        }
+      }
+      parent.children[i] = (label, child);
+    }
+    self.len -= 1;
+    value
+  }
+
+  /// Iterates the keys starting with `prefix` and their values, in key order.
+  pub fn starts_with(&self, prefix: &[E]) -> impl Iterator<Item = (Vec<E>, &V)> {
+    let mut stack = vec![];
+    let mut node = &self.root;
+    let mut key = vec![];
+    let mut rest = prefix;
+    loop {
+      let first = match rest.first() {
+        None => {
+          stack.push((key.len(), &[][..], node));
+          break;
+        }
+        Some(first) => first,
+      };
+      let (label, child) = match node.find(first) {
+        Ok(i) => &node.children[i],
+        Err(_) => break,
+      };
+      let common = label.iter().zip(rest).take_while(|(a, b)| a == b).count();
+      if common < label.len() && common < rest.len() {
+        break;
+      }
+      key.extend_from_slice(label);
+      node = child;
+      rest = &rest[common..];
+    }
+    std::iter::from_fn(move || {
+      // Key of the node being visited, shared along the traversal: each node is
+      // stacked with the length of the key of its parent and its label.
+      while let Some((depth, label, node)) = stack.pop() {
+        key.truncate(depth);
+        key.extend_from_slice(label);
+        stack.extend(node.children.iter().rev().map(|(label, child)| (key.len(), &label[..], child)));
+        if let Some(value) = &node.value {
+          return Some((key.clone(), value));
+        }
+      }
+      None
+    })
+  }
+
+  /// Iterates all keys and their values, in key order.
+  pub fn iter(&self) -> impl Iterator<Item = (Vec<E>, &V)> {
+    self.starts_with(&[])
+  }
+
+  /// Longest prefix of `key` that is in the trie, with its value.
+  pub fn longest_prefix<'a>(&self, key: &'a [E]) -> Option<(&'a [E], &V)> {
+    let mut node = &self.root;
+    let mut depth = 0;
+    let mut longest = node.value.as_ref().map(|v| (0, v));
+    while let Some(first) = key.get(depth) {
+      let (label, child) = match node.find(first) {
+        Ok(i) => &node.children[i],
+        Err(_) => break,
+      };
+      if !key[depth..].starts_with(label) {
+        break;
+      }
+      node = child;
+      depth += label.len();
+      longest = node.value.as_ref().map(|v| (depth, v)).or(longest);
+    }
+    longest.map(|(depth, v)| (&key[..depth], v))
+  }
+}
+
+impl<E, V> Node<E, V> {
+  fn new() -> Self {
+    Self { value: None, children: vec![] }
+  }
+
+  fn leaf(value: V) -> Self {
+    Self { value: Some(value), children: vec![] }
+  }
+
+  fn edge(label: Vec<E>, child: Self) -> Self {
+    Self { value: None, children: vec![(label, child)] }
+  }
+}
+
+impl<E: Ord + Clone, V> Node<E, V> {
+  fn find(&self, first: &E) -> Result<usize, usize> {
+    self.children.binary_search_by(|(label, _)| label[0].cmp(first))
+  }
+}
+
+impl<E, V> Drop for Node<E, V> {
+  fn drop(&mut self) {
+    let mut stack = mem::take(&mut self.children);
+    while let Some((_, mut node)) = stack.pop() {
+      stack.append(&mut node.children);
+    }
+  }
+}
+
+impl<E, V> Default for Trie<E, V> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<E: Ord + Clone + fmt::Debug, V: fmt::Debug> fmt::Debug for Trie<E, V> {
+  fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+    fmt.debug_map().entries(self.iter()).finish()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Trie;
+
+  #[test]
+  fn insert_get() {
+    for mut trie in [Trie::new(), Trie::compressed()] {
+      assert_eq!(trie.insert(b"tea", 1), None);
+      assert_eq!(trie.insert(b"ten", 2), None);
+      assert_eq!(trie.insert(b"te", 3), None);
+      assert_eq!(trie.insert(b"tea", 4), Some(1));
+      assert_eq!(trie.insert(b"", 5), None);
+      assert_eq!(trie.len(), 4);
+      assert_eq!(trie.get(b"tea"), Some(&4));
+      assert_eq!(trie.get(b"te"), Some(&3));
+      assert_eq!(trie.get(b""), Some(&5));
+      assert_eq!(trie.get(b"t"), None);
+      assert_eq!(trie.get(b"teal"), None);
+      *trie.get_mut(b"ten").unwrap() += 10;
+      assert_eq!(trie.get(b"ten"), Some(&12));
+    }
+  }
+
+  #[test]
+  fn remove() {
+    for mut trie in [new_trie(false), new_trie(true)] {
+      let nodes = trie.node_count();
+      assert_eq!(trie.remove(b"romane"), Some(1));
+      assert_eq!(trie.remove(b"romane"), None);
+      assert_eq!(trie.remove(b"rom"), None);
+      assert_eq!(trie.len(), 6);
+      assert!(trie.node_count() < nodes);
+      assert_eq!(trie.get(b"romanus"), Some(&2));
+      assert_eq!(trie.get(b"romulus"), Some(&3));
+      for key in ["romanus", "romulus", "rubens", "ruber", "rubicon", "rubicundus"].iter() {
+        assert!(trie.remove(key.as_bytes()).is_some());
+      }
+      assert!(trie.is_empty());
+      assert_eq!(trie.node_count(), 1);
+    }
+  }
+
+  #[test]
+  fn compressed() {
+    let (plain, radix) = (new_trie(false), new_trie(true));
+    assert_eq!(plain.node_count(), 28);
+    assert_eq!(radix.node_count(), 14);
+    assert!(plain.iter().eq(radix.iter()));
+    let mut radix = radix;
+    radix.remove(b"romanus");
+    assert_eq!(radix.node_count(), 12);
+    assert_eq!(radix.get(b"romane"), Some(&1));
+  }
+
+  #[test]
+  fn starts_with() {
+    let trie = new_trie(true);
+    let keys = |prefix: &[u8]| trie.starts_with(prefix).map(|(k, _)| String::from_utf8(k).unwrap()).collect::<Vec<_>>();
+    assert_eq!(keys(b"rom"), ["romane", "romanus", "romulus"]);
+    assert_eq!(keys(b"rube"), ["rubens", "ruber"]);
+    assert_eq!(keys(b"rubicon"), ["rubicon"]);
+    assert_eq!(keys(b"rx"), Vec::<String>::new());
+    assert_eq!(keys(b"rubiconx"), Vec::<String>::new());
+    assert_eq!(keys(b"").len(), 7);
+  }
+
+  #[test]
+  fn longest_prefix() {
+    let mut trie = Trie::compressed();
+    trie.insert(&['a', 'b'], 1);
+    trie.insert(&['a', 'b', 'c', 'd'], 2);
+    assert_eq!(trie.longest_prefix(&['a', 'b', 'c']), Some((&['a', 'b'][..], &1)));
+    assert_eq!(trie.longest_prefix(&['a', 'b', 'c', 'd', 'e']), Some((&['a', 'b', 'c', 'd'][..], &2)));
+    assert_eq!(trie.longest_prefix(&['a']), None);
+    trie.insert(&[], 0);
+    assert_eq!(trie.longest_prefix(&['x']), Some((&[][..], &0)));
+  }
+
+  #[test]
+  fn fmt() {
+    let mut trie = Trie::new();
+    trie.insert(&['b'], 2);
+    trie.insert(&['a'], 1);
+    assert_eq!(format!("{:?}", trie), "{['a']: 1, ['b']: 2}");
+  }
+
+  #[test]
+  fn long_keys() {
+    let key = vec![0u8; 1_000_000];
+    for mut trie in [Trie::new(), Trie::compressed()] {
+      trie.insert(&key, 1);
+      trie.insert(&key[..10], 2);
+      assert_eq!(trie.remove(&key), Some(1));
+      assert_eq!(trie.node_count(), if trie.is_compressed() { 2 } else { 11 });
+      trie.insert(&key[..500_000], 3);
+      assert_eq!(trie.get(&key[..500_000]), Some(&3));
+      let keys = trie.iter().map(|(k, _)| k.len()).collect::<Vec<_>>();
+      assert_eq!(keys, [10, 500_000]);
+      assert_eq!(trie.starts_with(&key[..20]).map(|(k, &v)| (k.len(), v)).collect::<Vec<_>>(), [(500_000, 3)]);
+    }
+  }
+
+  fn new_trie(compressed: bool) -> Trie<u8, i32> {
+    let mut trie = if compressed { Trie::compressed() } else { Trie::new() };
+    let keys = ["romane", "romanus", "romulus", "rubens", "ruber", "rubicon", "rubicundus"];
+    for (i, key) in keys.iter().enumerate() {
+      trie.insert(key.as_bytes(), i as i32 + 1);
+    }
+    trie
+  }
+}